@@ -0,0 +1,153 @@
+// Compares the directory walker as it was before the parallel rewrite with the current
+// single-threaded and parallel walkers on a generated fixture tree.
+// Run with: cargo bench --bench scan_walker
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use disk_maid::model::FileInfo;
+use disk_maid::scanner::{
+    aggregate_directory_sizes, scan_directory, CancelToken, ExcludeSet, FilterSet, ScanLimits, ScanOptions,
+};
+
+// 6 + 36 + 216 + 1296 directories with 20 files each
+const FANOUT: usize = 6;
+const DEPTH: usize = 4;
+const FILES_PER_DIR: usize = 20;
+const RUNS: usize = 5;
+
+// Removes the fixture tree even when a walker panics halfway through
+struct FixtureDir(PathBuf);
+
+impl FixtureDir {
+    fn new() -> Self {
+        let root = std::env::temp_dir().join(format!("disk_maid_bench_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("could not create fixture directory");
+        FixtureDir(root)
+    }
+}
+
+impl Drop for FixtureDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn build_fixture(dir: &Path, depth: usize) -> std::io::Result<()> {
+    for i in 0..FILES_PER_DIR {
+        fs::write(dir.join(format!("file_{}.dat", i)), vec![0u8; i * 64])?;
+    }
+    if depth == DEPTH {
+        return Ok(());
+    }
+    for i in 0..FANOUT {
+        let child = dir.join(format!("dir_{}", i));
+        fs::create_dir(&child)?;
+        build_fixture(&child, depth + 1)?;
+    }
+    Ok(())
+}
+
+// The walker the parallel one replaced: plain read_dir recursion, one stat per entry
+fn baseline_scan(root: &Path) -> Vec<FileInfo> {
+    fn scan_recursive(dir: &Path, files: &mut Vec<FileInfo>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let metadata = match fs::metadata(&path) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            let modified = metadata
+                .modified()
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            files.push(FileInfo {
+                path: path.to_string_lossy().to_string(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                is_dir: metadata.is_dir(),
+                modified,
+                file_count: 0,
+                content_type: None,
+            });
+            if metadata.is_dir() {
+                scan_recursive(&path, files);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    scan_recursive(root, &mut files);
+    aggregate_directory_sizes(&mut files);
+    files
+}
+
+fn median(mut timings: Vec<Duration>) -> Duration {
+    timings.sort();
+    timings[timings.len() / 2]
+}
+
+fn time_baseline(root: &Path) -> (Duration, usize) {
+    let mut timings = Vec::with_capacity(RUNS);
+    let mut entries = 0;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        entries = baseline_scan(root).len();
+        timings.push(started.elapsed());
+    }
+    (median(timings), entries)
+}
+
+fn time_walker(root: &Path, threads: usize) -> (Duration, usize) {
+    let options = ScanOptions {
+        filter: FilterSet::default(),
+        exclude: ExcludeSet::default(),
+        entry_filter: Default::default(),
+        limits: ScanLimits::default(),
+        threads,
+        detect_content: false,
+    };
+
+    let mut timings = Vec::with_capacity(RUNS);
+    let mut entries = 0;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        let result = scan_directory(root.to_path_buf(), &options, &CancelToken::default(), |_| {})
+            .expect("scan failed");
+        timings.push(started.elapsed());
+        entries = result.files.len();
+    }
+    (median(timings), entries)
+}
+
+fn main() {
+    let fixture = FixtureDir::new();
+    let root = &fixture.0;
+    build_fixture(root, 0).expect("could not build fixture tree");
+
+    // Warm the page cache so every walker sees the same conditions
+    baseline_scan(root);
+
+    let (baseline, baseline_entries) = time_baseline(root);
+    println!("previous walker:   {:>8.2?} median over {} runs ({} entries)", baseline, RUNS, baseline_entries);
+
+    let (sequential, sequential_entries) = time_walker(root, 1);
+    println!("sequential walker: {:>8.2?} median over {} runs ({} entries)", sequential, RUNS, sequential_entries);
+
+    let (parallel, parallel_entries) = time_walker(root, 0);
+    println!("parallel walker:   {:>8.2?} median over {} runs ({} entries)", parallel, RUNS, parallel_entries);
+
+    assert_eq!(baseline_entries, sequential_entries, "walkers disagree on the fixture tree");
+    assert_eq!(sequential_entries, parallel_entries, "walkers disagree on the fixture tree");
+    println!("speedup over the previous walker: {:.2}x", baseline.as_secs_f64() / parallel.as_secs_f64());
+    println!("speedup over threads = 1:         {:.2}x", sequential.as_secs_f64() / parallel.as_secs_f64());
+}
@@ -0,0 +1,1086 @@
+// Everything that changes the disk or writes files: delete, trash, move, undo and export
+
+use crate::model::FileInfo;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// --- DELETION ---
+
+// Outcome of a permanent delete; failures are collected instead of stopping at the first one
+#[derive(Debug, Clone, Default)]
+pub struct DeleteReport {
+    pub path: String,
+    pub removed: Vec<String>,
+    pub freed: u64,
+    pub failures: Vec<String>,
+}
+
+// Removes a file, or a directory with everything below it, as far as permissions allow
+pub fn delete_permanently(path: &Path) -> DeleteReport {
+    let mut report = DeleteReport {
+        path: path.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    // Directories that must stay because something inside them could not be removed
+    let mut kept: HashSet<PathBuf> = HashSet::new();
+
+    // A symlink root is a single entry: the link goes, whatever it points to stays
+    let walker = walkdir::WalkDir::new(path)
+        .contents_first(true)
+        .follow_links(false)
+        .follow_root_links(false);
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                if let Some(failed) = e.path() {
+                    kept.extend(failed.ancestors().map(Path::to_path_buf));
+                }
+                report.failures.push(e.to_string());
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && kept.contains(entry_path) {
+            continue;
+        }
+
+        let size = if is_dir { 0 } else { entry.metadata().map(|m| m.len()).unwrap_or(0) };
+        let removal = if is_dir { fs::remove_dir(entry_path) } else { fs::remove_file(entry_path) };
+        match removal {
+            Ok(()) => {
+                report.removed.push(entry_path.to_string_lossy().to_string());
+                report.freed += size;
+            }
+            Err(e) => {
+                kept.extend(entry_path.ancestors().map(Path::to_path_buf));
+                report.failures.push(format!("{}: {}", entry_path.display(), e));
+            }
+        }
+    }
+    report
+}
+
+// --- TRASH ---
+// Implements the freedesktop.org Trash specification: trashed items live in
+// `files/` next to a matching `info/<name>.trashinfo` holding the original path.
+
+// macOS and Windows keep their trash elsewhere; there every delete is permanent
+pub const TRASH_SUPPORTED: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+));
+
+// Where a trashed item ended up, enough to put it back later
+#[derive(Debug, Clone)]
+pub struct TrashedItem {
+    pub original: PathBuf,
+    pub trashed: PathBuf,
+    pub info: PathBuf,
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+pub fn move_to_trash(path: &Path) -> Result<TrashedItem, String> {
+    trash_into(path, dirs::data_dir().map(|dir| dir.join("Trash")))
+}
+
+// `home_trash` is `$XDG_DATA_HOME/Trash`; it is only created once the file turns out to share its filesystem
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+fn trash_into(path: &Path, home_trash: Option<PathBuf>) -> Result<TrashedItem, String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Cannot move {} to the Trash", path.display()))?;
+    // Resolve the parent only, so a trashed symlink stays a symlink
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    let original = parent.join(name);
+    let device = fs::symlink_metadata(&original).map_err(|e| e.to_string())?.dev();
+
+    // The home trash is used whenever it lives, or would be created, on the same filesystem as the file
+    let home_trash = home_trash.filter(|trash| {
+        let existing = trash.ancestors().find_map(|dir| fs::metadata(dir).ok());
+        existing.is_some_and(|meta| meta.dev() == device)
+    });
+
+    let (trash_dir, info_path) = if let Some(home_trash) = home_trash {
+        fs::create_dir_all(&home_trash).map_err(|e| e.to_string())?;
+        (home_trash, original.clone())
+    } else {
+        let top_dir = mount_top_dir(&parent, device);
+        let trash_dir = top_dir_trash(&top_dir)?;
+        // Paths inside a per-mount trash are stored relative to the mount point
+        let relative = original.strip_prefix(&top_dir).unwrap_or(&original).to_path_buf();
+        (trash_dir, relative)
+    };
+
+    let files_dir = trash_dir.join("files");
+    let info_dir = trash_dir.join("info");
+    fs::create_dir_all(&files_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&info_dir).map_err(|e| e.to_string())?;
+
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode_path(&info_path),
+        local_timestamp()
+    );
+
+    // Creating the .trashinfo with create_new reserves the name atomically
+    let name = name.to_string_lossy().to_string();
+    for attempt in 1u32.. {
+        let candidate = if attempt == 1 { name.clone() } else { format!("{}.{}", name, attempt) };
+        let info = info_dir.join(format!("{}.trashinfo", candidate));
+        let trashed = files_dir.join(&candidate);
+        if fs::symlink_metadata(&trashed).is_ok() {
+            continue;
+        }
+        let mut info_file = match fs::OpenOptions::new().write(true).create_new(true).open(&info) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        use std::io::Write;
+        if let Err(e) = info_file.write_all(contents.as_bytes()) {
+            let _ = fs::remove_file(&info);
+            return Err(e.to_string());
+        }
+        if let Err(e) = fs::rename(&original, &trashed) {
+            let _ = fs::remove_file(&info);
+            return Err(e.to_string());
+        }
+        return Ok(TrashedItem { original, trashed, info });
+    }
+    unreachable!("ran out of trash names")
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+pub fn move_to_trash(_path: &Path) -> Result<TrashedItem, String> {
+    Err("The Trash is not supported on this platform".into())
+}
+
+// Highest ancestor that is still on `device`, i.e. the mount point
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+fn mount_top_dir(dir: &Path, device: u64) -> PathBuf {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut top = dir.to_path_buf();
+    for ancestor in dir.ancestors() {
+        match fs::metadata(ancestor) {
+            Ok(meta) if meta.dev() == device => top = ancestor.to_path_buf(),
+            _ => break,
+        }
+    }
+    top
+}
+
+// `$topdir/.Trash/$uid` if the admin set up a shared sticky .Trash, otherwise `$topdir/.Trash-$uid`
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+fn top_dir_trash(top_dir: &Path) -> Result<PathBuf, String> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let uid = unsafe { libc::getuid() };
+
+    let shared = top_dir.join(".Trash");
+    if let Ok(meta) = fs::symlink_metadata(&shared) {
+        let sticky = meta.permissions().mode() & 0o1000 != 0;
+        if meta.is_dir() && !meta.file_type().is_symlink() && sticky {
+            let user_trash = shared.join(uid.to_string());
+            if fs::DirBuilder::new().mode(0o700).create(&user_trash).is_ok() || user_trash.is_dir() {
+                return Ok(user_trash);
+            }
+        }
+    }
+
+    let user_trash = top_dir.join(format!(".Trash-{}", uid));
+    match fs::DirBuilder::new().mode(0o700).create(&user_trash) {
+        Ok(()) => Ok(user_trash),
+        Err(_) if user_trash.is_dir() => Ok(user_trash),
+        Err(e) => Err(format!("No usable Trash on {}: {}", top_dir.display(), e)),
+    }
+}
+
+// RFC 2396 escaping as required for the Path= key, keeping '/' readable
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+fn percent_encode_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// DeletionDate uses local time in YYYY-MM-DDThh:mm:ss form
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+fn local_timestamp() -> String {
+    use std::time::SystemTime;
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::localtime_r(&now, &mut tm) };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+// --- BATCH OPERATIONS ---
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchAction {
+    Trash,
+    Delete,
+    Move(PathBuf),
+}
+
+impl std::fmt::Display for BatchAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchAction::Trash => write!(f, "Move to Trash"),
+            BatchAction::Delete => write!(f, "Delete permanently"),
+            BatchAction::Move(dest) => write!(f, "Move to {}", dest.display()),
+        }
+    }
+}
+
+// Where a moved entry ended up, enough to put it back later
+#[derive(Debug, Clone)]
+pub struct MovedItem {
+    pub original: PathBuf,
+    pub moved_to: PathBuf,
+}
+
+// Combined outcome of one batch; every entry is attempted even if earlier ones fail
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    pub attempted: usize,
+    pub completed: usize,
+    // Paths that are no longer where the scan found them (descendants implied)
+    pub removed: Vec<String>,
+    pub bytes: u64,
+    // Trashed and moved entries by their scanned path, for the undo journal
+    pub reversible: Vec<(String, Reversible)>,
+    pub failures: Vec<String>,
+}
+
+pub fn run_batch(action: &BatchAction, entries: &[FileInfo]) -> BatchReport {
+    let mut report = BatchReport {
+        attempted: entries.len(),
+        ..Default::default()
+    };
+
+    for entry in entries {
+        let path = Path::new(&entry.path);
+        match action {
+            BatchAction::Delete => {
+                let deleted = delete_permanently(path);
+                if deleted.failures.is_empty() {
+                    report.completed += 1;
+                }
+                report.removed.extend(deleted.removed);
+                report.bytes += deleted.freed;
+                report.failures.extend(deleted.failures);
+            }
+            BatchAction::Trash => match move_to_trash(path) {
+                Ok(item) => {
+                    report.completed += 1;
+                    report.removed.push(entry.path.clone());
+                    report.bytes += entry.size;
+                    report.reversible.push((entry.path.clone(), Reversible::Trashed(item)));
+                }
+                Err(e) => report.failures.push(format!("{}: {}", entry.path, e)),
+            },
+            BatchAction::Move(dest) => match move_path(path, dest) {
+                Ok(moved_to) => {
+                    report.completed += 1;
+                    report.removed.push(entry.path.clone());
+                    report.bytes += entry.size;
+                    let item = MovedItem {
+                        original: path.to_path_buf(),
+                        moved_to,
+                    };
+                    report.reversible.push((entry.path.clone(), Reversible::Moved(item)));
+                }
+                Err(e) => report.failures.push(format!("{}: {}", entry.path, e)),
+            },
+        }
+    }
+    report
+}
+
+// Moves a file or directory into `dest_dir`, falling back to copy + delete across filesystems
+pub fn move_path(path: &Path, dest_dir: &Path) -> Result<PathBuf, String> {
+    let name = path.file_name().ok_or_else(|| "cannot move a filesystem root".to_string())?;
+    let target = dest_dir.join(name);
+    if target.starts_with(path) {
+        return Err("cannot move a folder into itself".into());
+    }
+    if fs::symlink_metadata(&target).is_ok() {
+        return Err(format!("{} already exists", target.display()));
+    }
+
+    match fs::rename(path, &target) {
+        Ok(()) => Ok(target),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => move_by_copy(path, target),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// The cross-filesystem half of move_path: the source is only removed once the copy is complete
+fn move_by_copy(path: &Path, target: PathBuf) -> Result<PathBuf, String> {
+    let mut created = Vec::new();
+    if let Err(e) = copy_recursively(path, &target, &mut created) {
+        // Leave the source alone and drop the partial copy; anything that appeared at the
+        // target in the meantime belongs to someone else and stays
+        remove_created(&created);
+        return Err(e);
+    }
+    let report = delete_permanently(path);
+    match report.failures.first() {
+        None => Ok(target),
+        Some(first) => Err(format!(
+            "copied to {} but the original could not be fully removed: {}",
+            target.display(),
+            first
+        )),
+    }
+}
+
+// Every entry is created exclusively, starting with `target` itself, so the copy fails
+// instead of writing into anything that already exists. `created` lists what it made.
+fn copy_recursively(source: &Path, target: &Path, created: &mut Vec<PathBuf>) -> Result<(), String> {
+    for entry in walkdir::WalkDir::new(source).follow_links(false).follow_root_links(false) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let relative = entry.path().strip_prefix(source).map_err(|e| e.to_string())?;
+        let destination = target.join(relative);
+        let file_type = entry.file_type();
+        let copied = if file_type.is_dir() {
+            fs::create_dir(&destination).map(|()| created.push(destination))
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), destination, created)
+        } else {
+            copy_file(entry.path(), destination, created)
+        };
+        copied.map_err(|e| format!("{}: {}", entry.path().display(), e))?;
+    }
+    Ok(())
+}
+
+fn copy_file(source: &Path, destination: PathBuf, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut from = fs::File::open(source)?;
+    let mut to = fs::OpenOptions::new().write(true).create_new(true).open(&destination)?;
+    created.push(destination);
+    std::io::copy(&mut from, &mut to)?;
+    to.set_permissions(from.metadata()?.permissions())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: PathBuf, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, &destination)?;
+    created.push(destination);
+    Ok(())
+}
+
+// Without portable symlinks the link's target is copied instead
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, destination: PathBuf, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    copy_file(source, destination, created)
+}
+
+// Undoes a failed copy_recursively, deepest entries first. A folder that someone else put
+// something into in the meantime is not empty and stays.
+fn remove_created(created: &[PathBuf]) {
+    for path in created.iter().rev() {
+        let _ = match fs::symlink_metadata(path) {
+            Ok(meta) if meta.is_dir() => fs::remove_dir(path),
+            _ => fs::remove_file(path),
+        };
+    }
+}
+
+// --- UNDO JOURNAL ---
+
+// A trash or move that can be put back where it came from
+#[derive(Debug, Clone)]
+pub enum Reversible {
+    Trashed(TrashedItem),
+    Moved(MovedItem),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    Restored,
+    // Something new now occupies the original path; nothing was touched
+    Conflict,
+    Failed(String),
+}
+
+impl Reversible {
+    pub fn original(&self) -> &Path {
+        match self {
+            Reversible::Trashed(item) => &item.original,
+            Reversible::Moved(item) => &item.original,
+        }
+    }
+
+    pub fn restore(&self) -> RestoreOutcome {
+        let original = self.original();
+        if fs::symlink_metadata(original).is_ok() {
+            return RestoreOutcome::Conflict;
+        }
+        // The original folder may have been removed since
+        let Some(parent) = original.parent() else {
+            return RestoreOutcome::Failed(format!("{} has no parent folder", original.display()));
+        };
+        if let Err(e) = fs::create_dir_all(parent) {
+            return RestoreOutcome::Failed(format!("{}: {}", parent.display(), e));
+        }
+
+        let restored = match self {
+            Reversible::Trashed(item) => fs::rename(&item.trashed, original)
+                .map(|()| {
+                    let _ = fs::remove_file(&item.info);
+                })
+                .map_err(|e| format!("{}: {}", item.trashed.display(), e)),
+            // Moves keep the file name, so moving back into the parent restores the original path
+            Reversible::Moved(item) => move_path(&item.moved_to, parent)
+                .map(|_| ())
+                .map_err(|e| format!("{}: {}", item.moved_to.display(), e)),
+        };
+        match restored {
+            Ok(()) => RestoreOutcome::Restored,
+            Err(e) => RestoreOutcome::Failed(e),
+        }
+    }
+}
+
+// One undoable step of this session: a single trash or a whole batch
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub description: String,
+    pub items: Vec<JournalItem>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalItem {
+    pub path: String,
+    pub action: Reversible,
+    // Scan entries dropped from the results, put back when the item is restored
+    pub files: Vec<FileInfo>,
+}
+
+// Restores the items newest first so nested moves unwind in order
+pub fn undo_journal_entry(entry: &JournalEntry) -> Vec<RestoreOutcome> {
+    let mut outcomes: Vec<RestoreOutcome> = entry.items.iter().rev().map(|item| item.action.restore()).collect();
+    outcomes.reverse();
+    outcomes
+}
+
+// --- EXPORT ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    // Picked from the extension typed in the save dialog; CSV unless it ends in .json
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    // "report.v2" saved as JSON becomes "report.v2.json"; "out.JSON" is left alone
+    pub fn with_extension(self, path: &str) -> String {
+        let extension = Path::new(path).extension().and_then(|e| e.to_str());
+        if extension.is_some_and(|e| e.eq_ignore_ascii_case(self.extension())) {
+            path.to_string()
+        } else {
+            format!("{}.{}", path, self.extension())
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "CSV"),
+            ExportFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+// One exported row; the field names double as the CSV header
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportRecord {
+    pub path: String,
+    pub size: u64,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    // ISO 8601, UTC
+    pub modified: String,
+    pub is_dir: bool,
+}
+
+impl ExportRecord {
+    pub fn new(file: &FileInfo) -> Self {
+        let file_type = if file.is_dir {
+            "Folder".to_string()
+        } else if let Some(content) = file.content_type {
+            content.to_string()
+        } else {
+            file.category().to_string()
+        };
+        Self {
+            path: file.path.clone(),
+            size: file.size,
+            file_type,
+            modified: format_timestamp(file.modified),
+            is_dir: file.is_dir,
+        }
+    }
+}
+
+// Unix seconds to "YYYY-MM-DDTHH:MM:SSZ" (inverse of the date math in parse_date)
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn format_export(format: ExportFormat, records: &[ExportRecord]) -> Result<String, String> {
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(records).map_err(|e| e.to_string())?,
+        ExportFormat::Csv => {
+            let mut csv = String::from("path,size,type,modified,is_dir\n");
+            for record in records {
+                csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(&record.path),
+                    record.size,
+                    csv_field(&record.file_type),
+                    record.modified,
+                    record.is_dir
+                ));
+            }
+            csv
+        }
+    })
+}
+
+pub fn export_results(path: &Path, format: ExportFormat, records: &[ExportRecord]) -> Result<(), String> {
+    let content = format_export(format, records)?;
+    fs::write(path, content).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fresh directory under the system temp dir, unique per test and process
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("disk_maid_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn scanned(path: &Path, is_dir: bool, size: u64) -> FileInfo {
+        FileInfo {
+            path: path.to_string_lossy().to_string(),
+            size,
+            is_dir,
+            modified: 0,
+            file_count: 0,
+            content_type: None,
+        }
+    }
+
+    fn journal(report: &BatchReport) -> JournalEntry {
+        JournalEntry {
+            description: "test".to_string(),
+            items: report
+                .reversible
+                .iter()
+                .map(|(path, action)| JournalItem {
+                    path: path.clone(),
+                    action: action.clone(),
+                    files: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn record(path: &str, file_type: &str) -> ExportRecord {
+        ExportRecord {
+            path: path.to_string(),
+            size: 42,
+            file_type: file_type.to_string(),
+            modified: format_timestamp(0),
+            is_dir: false,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn deleting_a_symlinked_folder_removes_only_the_link() {
+        let dir = std::env::temp_dir().join(format!("disk_maid_delete_link_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("target/file"), b"keep me").unwrap();
+        std::os::unix::fs::symlink(dir.join("target"), dir.join("link")).unwrap();
+
+        let report = delete_permanently(&dir.join("link"));
+        let target_kept = dir.join("target/file").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.removed.len(), 1);
+        assert!(target_kept);
+    }
+
+    #[test]
+    fn batch_move_carries_whole_folder_trees() {
+        let dir = fixture("batch_move_tree");
+        fs::create_dir_all(dir.join("tree/a/b")).unwrap();
+        fs::create_dir(dir.join("dest")).unwrap();
+        fs::write(dir.join("tree/a/b/deep"), b"deep").unwrap();
+        fs::write(dir.join("tree/top"), b"top").unwrap();
+
+        let action = BatchAction::Move(dir.join("dest"));
+        let report = run_batch(&action, &[scanned(&dir.join("tree"), true, 7)]);
+        let moved = (fs::read(dir.join("dest/tree/a/b/deep")).unwrap(), fs::read(dir.join("dest/tree/top")).unwrap());
+        let source_left = dir.join("tree").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!((report.attempted, report.completed, report.bytes), (1, 1, 7));
+        assert_eq!(report.removed, vec![dir.join("tree").to_string_lossy().to_string()]);
+        assert_eq!(moved, (b"deep".to_vec(), b"top".to_vec()));
+        assert!(!source_left);
+        assert!(matches!(&report.reversible[..], [(_, Reversible::Moved(item))] if item.moved_to == dir.join("dest/tree")));
+    }
+
+    #[test]
+    fn batch_move_never_overwrites_the_destination() {
+        let dir = fixture("batch_move_conflict");
+        fs::create_dir(dir.join("dest")).unwrap();
+        fs::write(dir.join("report.txt"), b"new").unwrap();
+        fs::write(dir.join("dest/report.txt"), b"old").unwrap();
+
+        let action = BatchAction::Move(dir.join("dest"));
+        let report = run_batch(&action, &[scanned(&dir.join("report.txt"), false, 3)]);
+        let contents = (fs::read(dir.join("report.txt")).unwrap(), fs::read(dir.join("dest/report.txt")).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.completed, 0);
+        assert!(report.removed.is_empty());
+        assert!(report.reversible.is_empty());
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].contains("already exists"), "{}", report.failures[0]);
+        assert_eq!(contents, (b"new".to_vec(), b"old".to_vec()));
+    }
+
+    #[test]
+    fn batch_report_lists_only_what_left_the_disk() {
+        let dir = fixture("batch_mixed");
+        fs::create_dir(dir.join("dest")).unwrap();
+        fs::write(dir.join("moves"), b"1").unwrap();
+        fs::write(dir.join("clashes"), b"2").unwrap();
+        fs::write(dir.join("dest/clashes"), b"3").unwrap();
+        let entries = [
+            scanned(&dir.join("moves"), false, 1),
+            scanned(&dir.join("clashes"), false, 1),
+            scanned(&dir.join("missing"), false, 1),
+        ];
+
+        let report = run_batch(&BatchAction::Move(dir.join("dest")), &entries);
+        let still_there = (dir.join("moves").exists(), dir.join("clashes").exists());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((report.attempted, report.completed, report.bytes), (3, 1, 1));
+        assert_eq!(report.removed, vec![entries[0].path.clone()]);
+        assert_eq!(report.reversible.len(), 1);
+        assert_eq!(report.failures.len(), 2, "{:?}", report.failures);
+        assert_eq!(still_there, (false, true));
+    }
+
+    #[test]
+    fn undo_moves_entries_back_and_recreates_missing_folders() {
+        let dir = fixture("undo_move");
+        fs::create_dir_all(dir.join("src/sub")).unwrap();
+        fs::create_dir(dir.join("dest")).unwrap();
+        fs::write(dir.join("src/sub/file"), b"one").unwrap();
+        fs::write(dir.join("src/other"), b"two").unwrap();
+        let entries = [scanned(&dir.join("src/sub/file"), false, 3), scanned(&dir.join("src/other"), false, 3)];
+
+        let report = run_batch(&BatchAction::Move(dir.join("dest")), &entries);
+        // The folder the first file came from is gone by the time the user undoes
+        fs::remove_dir(dir.join("src/sub")).unwrap();
+        let outcomes = undo_journal_entry(&journal(&report));
+        let restored = (fs::read(dir.join("src/sub/file")).unwrap(), fs::read(dir.join("src/other")).unwrap());
+        let dest_empty = fs::read_dir(dir.join("dest")).unwrap().next().is_none();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(outcomes, vec![RestoreOutcome::Restored, RestoreOutcome::Restored]);
+        assert_eq!(restored, (b"one".to_vec(), b"two".to_vec()));
+        assert!(dest_empty);
+    }
+
+    #[test]
+    fn undo_leaves_both_copies_alone_when_the_original_path_is_taken() {
+        let dir = fixture("undo_conflict");
+        fs::create_dir(dir.join("dest")).unwrap();
+        fs::write(dir.join("taken"), b"moved away").unwrap();
+        fs::write(dir.join("free"), b"comes back").unwrap();
+        let entries = [scanned(&dir.join("taken"), false, 10), scanned(&dir.join("free"), false, 10)];
+
+        let report = run_batch(&BatchAction::Move(dir.join("dest")), &entries);
+        fs::write(dir.join("taken"), b"newer file").unwrap();
+        let outcomes = undo_journal_entry(&journal(&report));
+        let taken = (fs::read(dir.join("taken")).unwrap(), fs::read(dir.join("dest/taken")).unwrap());
+        let free = (fs::read(dir.join("free")).unwrap(), dir.join("dest/free").exists());
+        fs::remove_dir_all(&dir).unwrap();
+
+        // One outcome per item, in the order the items were journaled
+        assert_eq!(outcomes, vec![RestoreOutcome::Conflict, RestoreOutcome::Restored]);
+        assert_eq!(taken, (b"newer file".to_vec(), b"moved away".to_vec()));
+        assert_eq!(free, (b"comes back".to_vec(), false));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn move_by_copy_copies_the_tree_then_removes_the_source() {
+        let dir = fixture("move_by_copy");
+        fs::create_dir_all(dir.join("tree/sub")).unwrap();
+        fs::write(dir.join("tree/sub/file"), b"copied").unwrap();
+        std::os::unix::fs::symlink("sub/file", dir.join("tree/link")).unwrap();
+
+        let target = move_by_copy(&dir.join("tree"), dir.join("copy")).unwrap();
+        let copied = fs::read(dir.join("copy/sub/file")).unwrap();
+        let link = fs::read_link(dir.join("copy/link")).unwrap();
+        let source_left = dir.join("tree").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(target, dir.join("copy"));
+        assert_eq!(copied, b"copied");
+        // Links are copied as links, not as the file they point to
+        assert_eq!(link, PathBuf::from("sub/file"));
+        assert!(!source_left);
+    }
+
+    #[test]
+    fn copy_never_touches_a_target_that_appeared_after_the_check() {
+        let dir = fixture("move_by_copy_taken");
+        fs::create_dir_all(dir.join("tree/sub")).unwrap();
+        fs::write(dir.join("tree/sub/file"), b"source").unwrap();
+        // Created between move_path's existence check and the copy
+        fs::create_dir_all(dir.join("copy/sub")).unwrap();
+        fs::write(dir.join("copy/sub/file"), b"theirs").unwrap();
+
+        let result = move_by_copy(&dir.join("tree"), dir.join("copy"));
+        let theirs = fs::read(dir.join("copy/sub/file")).unwrap();
+        let source = fs::read(dir.join("tree/sub/file")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(theirs, b"theirs");
+        assert_eq!(source, b"source");
+    }
+
+    #[test]
+    fn failed_copy_keeps_the_source() {
+        let dir = fixture("move_by_copy_failed");
+        fs::create_dir(dir.join("tree")).unwrap();
+        fs::write(dir.join("tree/file"), b"safe").unwrap();
+
+        let result = move_by_copy(&dir.join("tree"), dir.join("missing/parent/tree"));
+        let kept = fs::read(dir.join("tree/file")).unwrap();
+        let partial_left = dir.join("missing").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(kept, b"safe");
+        assert!(!partial_left);
+    }
+
+    #[test]
+    fn format_timestamp_writes_iso_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_868_799), "2000-02-29T23:59:59Z");
+        assert_eq!(format_timestamp(1_709_164_800 + 3_723), "2024-02-29T01:02:03Z");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain.txt"), "plain.txt");
+        assert_eq!(csv_field("a,b.txt"), "\"a,b.txt\"");
+        assert_eq!(csv_field("say \"hi\".txt"), "\"say \"\"hi\"\".txt\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_export_has_a_header_and_one_row_per_record() {
+        let csv = format_export(ExportFormat::Csv, &[record("/data/a,b.txt", "Document")]).unwrap();
+        assert_eq!(
+            csv,
+            "path,size,type,modified,is_dir\n\"/data/a,b.txt\",42,Document,1970-01-01T00:00:00Z,false\n"
+        );
+    }
+
+    #[test]
+    fn json_export_uses_the_type_field_name() {
+        let json = format_export(ExportFormat::Json, &[record("/data/a.txt", "Document")]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["type"], "Document");
+        assert_eq!(value[0]["path"], "/data/a.txt");
+        assert_eq!(value[0]["size"], 42);
+    }
+
+    #[test]
+    fn export_paths_end_in_the_chosen_format() {
+        assert_eq!(ExportFormat::Json.with_extension("/out/report.v2"), "/out/report.v2.json");
+        assert_eq!(ExportFormat::Json.with_extension("/out/report.csv"), "/out/report.csv.json");
+        assert_eq!(ExportFormat::Json.with_extension("/out/report.JSON"), "/out/report.JSON");
+        assert_eq!(ExportFormat::Csv.with_extension("/out/report"), "/out/report.csv");
+        assert_eq!(ExportFormat::Csv.with_extension("/out/report.csv"), "/out/report.csv");
+    }
+
+    #[test]
+    fn export_format_follows_the_extension() {
+        assert_eq!(ExportFormat::from_path(Path::new("out.JSON")), ExportFormat::Json);
+        assert_eq!(ExportFormat::from_path(Path::new("out.csv")), ExportFormat::Csv);
+        assert_eq!(ExportFormat::from_path(Path::new("out")), ExportFormat::Csv);
+    }
+
+    // The home trash sits inside each fixture, so it always shares the files' filesystem
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+    mod trash {
+        use super::*;
+
+        #[test]
+        fn trashinfo_holds_the_encoded_path_and_deletion_date() {
+            let dir = fixture("trash_info");
+            let canonical = dir.canonicalize().unwrap();
+            let file = dir.join("my file%.txt");
+            fs::write(&file, b"bytes").unwrap();
+
+            let item = trash_into(&file, Some(dir.join("Trash"))).unwrap();
+            let info = fs::read_to_string(&item.info).unwrap();
+            let trashed = fs::read(&item.trashed).unwrap();
+            let original_gone = !file.exists();
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(item.trashed, dir.join("Trash/files/my file%.txt"));
+            assert_eq!(item.info, dir.join("Trash/info/my file%.txt.trashinfo"));
+            assert_eq!(trashed, b"bytes");
+            assert!(original_gone);
+
+            let lines: Vec<&str> = info.lines().collect();
+            assert_eq!(lines.len(), 3, "{}", info);
+            assert_eq!(lines[0], "[Trash Info]");
+            assert_eq!(lines[1], format!("Path={}/my%20file%25.txt", canonical.display()));
+            // YYYY-MM-DDThh:mm:ss, local time without a zone
+            let date = lines[2].strip_prefix("DeletionDate=").unwrap();
+            assert_eq!(date.len(), 19, "{}", date);
+            for (i, c) in date.char_indices() {
+                match i {
+                    4 | 7 => assert_eq!(c, '-', "{}", date),
+                    10 => assert_eq!(c, 'T', "{}", date),
+                    13 | 16 => assert_eq!(c, ':', "{}", date),
+                    _ => assert!(c.is_ascii_digit(), "{}", date),
+                }
+            }
+        }
+
+        #[test]
+        fn percent_encoding_keeps_slashes_and_unreserved_bytes() {
+            assert_eq!(percent_encode_path(Path::new("/data/a-b_c.~d")), "/data/a-b_c.~d");
+            assert_eq!(percent_encode_path(Path::new("/data/a b/ü#?.txt")), "/data/a%20b/%C3%BC%23%3F.txt");
+        }
+
+        #[test]
+        fn name_collisions_get_a_numbered_suffix() {
+            let dir = fixture("trash_collision");
+            fs::create_dir(dir.join("x")).unwrap();
+            fs::create_dir(dir.join("y")).unwrap();
+            fs::write(dir.join("x/f1"), b"first").unwrap();
+            fs::write(dir.join("y/f1"), b"second").unwrap();
+
+            let first = trash_into(&dir.join("x/f1"), Some(dir.join("Trash"))).unwrap();
+            let second = trash_into(&dir.join("y/f1"), Some(dir.join("Trash"))).unwrap();
+            let contents = (fs::read(&first.trashed).unwrap(), fs::read(&second.trashed).unwrap());
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(first.trashed, dir.join("Trash/files/f1"));
+            assert_eq!(second.trashed, dir.join("Trash/files/f1.2"));
+            assert_eq!(second.info, dir.join("Trash/info/f1.2.trashinfo"));
+            assert_eq!(contents, (b"first".to_vec(), b"second".to_vec()));
+        }
+
+        #[test]
+        fn trashed_folders_come_back_on_restore() {
+            let dir = fixture("trash_restore");
+            fs::create_dir_all(dir.join("folder/inner")).unwrap();
+            fs::write(dir.join("folder/inner/file"), b"keep").unwrap();
+
+            let item = trash_into(&dir.join("folder"), Some(dir.join("Trash"))).unwrap();
+            let info = item.info.clone();
+            let outcome = Reversible::Trashed(item).restore();
+            let restored = fs::read(dir.join("folder/inner/file")).unwrap();
+            let info_left = info.exists();
+            let trash_empty = fs::read_dir(dir.join("Trash/files")).unwrap().next().is_none();
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(outcome, RestoreOutcome::Restored);
+            assert_eq!(restored, b"keep");
+            assert!(!info_left);
+            assert!(trash_empty);
+        }
+
+        #[test]
+        fn undo_restores_every_trashed_item_of_an_entry() {
+            let dir = fixture("undo_trash");
+            fs::create_dir(dir.join("folder")).unwrap();
+            fs::write(dir.join("folder/inside"), b"a").unwrap();
+            fs::write(dir.join("same"), b"b").unwrap();
+            fs::create_dir(dir.join("elsewhere")).unwrap();
+            fs::write(dir.join("elsewhere/same"), b"c").unwrap();
+
+            let items: Vec<JournalItem> = ["folder", "same", "elsewhere/same"]
+                .iter()
+                .map(|name| JournalItem {
+                    path: dir.join(name).to_string_lossy().to_string(),
+                    action: Reversible::Trashed(trash_into(&dir.join(name), Some(dir.join("Trash"))).unwrap()),
+                    files: Vec::new(),
+                })
+                .collect();
+            let gone = ["folder", "same", "elsewhere/same"].iter().all(|name| !dir.join(name).exists());
+            let entry = JournalEntry {
+                description: "Move to Trash: 3 items".to_string(),
+                items,
+            };
+            let outcomes = undo_journal_entry(&entry);
+            let restored: Vec<Vec<u8>> = ["folder/inside", "same", "elsewhere/same"]
+                .iter()
+                .map(|name| fs::read(dir.join(name)).unwrap())
+                .collect();
+            let trash_files = fs::read_dir(dir.join("Trash/files")).unwrap().count();
+            let trash_info = fs::read_dir(dir.join("Trash/info")).unwrap().count();
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert!(gone);
+            assert_eq!(outcomes, vec![RestoreOutcome::Restored; 3]);
+            assert_eq!(restored, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+            assert_eq!((trash_files, trash_info), (0, 0));
+        }
+
+        #[test]
+        fn per_mount_trash_is_private_to_the_user() {
+            use std::os::unix::fs::PermissionsExt;
+
+            let dir = fixture("top_dir_trash");
+            let trash = top_dir_trash(&dir).unwrap();
+            let mode = fs::metadata(&trash).unwrap().permissions().mode() & 0o777;
+            let again = top_dir_trash(&dir).unwrap();
+
+            // An admin-provided sticky .Trash is shared, with one folder per user
+            fs::create_dir(dir.join(".Trash")).unwrap();
+            fs::set_permissions(dir.join(".Trash"), fs::Permissions::from_mode(0o1777)).unwrap();
+            let shared = top_dir_trash(&dir).unwrap();
+            fs::remove_dir_all(&dir).unwrap();
+
+            let uid = unsafe { libc::getuid() };
+            assert_eq!(trash, dir.join(format!(".Trash-{}", uid)));
+            assert_eq!(mode, 0o700);
+            assert_eq!(again, trash);
+            assert_eq!(shared, dir.join(".Trash").join(uid.to_string()));
+        }
+
+        #[test]
+        fn mount_top_dir_stops_at_the_filesystem_boundary() {
+            use std::os::unix::fs::MetadataExt;
+
+            let dir = fixture("mount_top").canonicalize().unwrap();
+            let device = fs::metadata(&dir).unwrap().dev();
+            let top = mount_top_dir(&dir, device);
+            fs::remove_dir_all(&dir).unwrap();
+
+            assert!(dir.starts_with(&top));
+            assert_eq!(fs::metadata(&top).unwrap().dev(), device);
+            if let Some(parent) = top.parent() {
+                assert_ne!(fs::metadata(parent).unwrap().dev(), device);
+            }
+        }
+    }
+}
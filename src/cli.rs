@@ -0,0 +1,497 @@
+// Headless subcommands sharing the scan engine with the GUI
+
+#[cfg(feature = "tui")]
+use crate::tui::run_tui;
+use disk_maid::actions::{export_results, format_export, ExportFormat, ExportRecord};
+use disk_maid::config::{AppConfig, Unit};
+use disk_maid::model::{size_label, sort_files, FileInfo, ScanResult, SortMethod};
+use disk_maid::scanner::{find_duplicates, scan_directory, CancelToken};
+use std::{io::Write, path::PathBuf, time::Instant};
+
+// --- COMMAND LINE ---
+
+// Without a subcommand Disk Maid opens the GUI
+#[derive(Debug, clap::Parser)]
+#[command(name = "disk_maid", about = "Find out what is using your disk space", version)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Option<CliCommand>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub(crate) enum CliCommand {
+    /// Scan a folder and print totals
+    Scan {
+        #[command(flatten)]
+        scan: CliScanArgs,
+    },
+    /// List the largest files and folders
+    Top {
+        #[command(flatten)]
+        scan: CliScanArgs,
+        /// Number of entries to print
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// Only list files
+        #[arg(long, conflicts_with = "dirs_only")]
+        files_only: bool,
+        /// Only list folders
+        #[arg(long)]
+        dirs_only: bool,
+    },
+    /// Find files with identical content
+    Dupes {
+        #[command(flatten)]
+        scan: CliScanArgs,
+    },
+    /// Write every scanned entry as CSV or JSON
+    Export {
+        #[command(flatten)]
+        scan: CliScanArgs,
+        /// Output file; standard output when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Defaults to the output file's extension, or CSV
+        #[arg(long, value_enum)]
+        format: Option<CliExportFormat>,
+        /// Order of the rows; defaults to the sort saved in Settings
+        #[arg(long, value_enum)]
+        sort: Option<CliSortMethod>,
+    },
+    /// Browse a scan in the terminal, ncdu style
+    #[cfg(feature = "tui")]
+    Tui {
+        #[command(flatten)]
+        scan: CliScanArgs,
+    },
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub(crate) enum CliExportFormat {
+    Csv,
+    Json,
+}
+
+// Command-line names for the library's enums, so the library itself does not depend on clap
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub(crate) enum CliSortMethod {
+    NameAZ,
+    NameZA,
+    SizeLargest,
+    SizeSmallest,
+    Newest,
+    Oldest,
+}
+
+impl From<CliSortMethod> for SortMethod {
+    fn from(sort: CliSortMethod) -> Self {
+        match sort {
+            CliSortMethod::NameAZ => SortMethod::NameAZ,
+            CliSortMethod::NameZA => SortMethod::NameZA,
+            CliSortMethod::SizeLargest => SortMethod::SizeLargest,
+            CliSortMethod::SizeSmallest => SortMethod::SizeSmallest,
+            CliSortMethod::Newest => SortMethod::Newest,
+            CliSortMethod::Oldest => SortMethod::Oldest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub(crate) enum CliUnit {
+    KB,
+    MB,
+    GB,
+}
+
+impl From<CliUnit> for Unit {
+    fn from(unit: CliUnit) -> Self {
+        match unit {
+            CliUnit::KB => Unit::KB,
+            CliUnit::MB => Unit::MB,
+            CliUnit::GB => Unit::GB,
+        }
+    }
+}
+
+// Scan settings shared by every subcommand; anything not given comes from the base settings
+#[derive(Debug, clap::Args)]
+pub(crate) struct CliScanArgs {
+    /// Folder to scan; defaults to the default path from Settings
+    path: Option<PathBuf>,
+    /// Filename filter, e.g. "*.{mp4,mkv}, !*.part"
+    #[arg(long)]
+    filter: Option<String>,
+    /// Extra path or name to skip (repeatable)
+    #[arg(long)]
+    exclude: Vec<String>,
+    #[arg(long)]
+    max_depth: Option<usize>,
+    #[arg(long)]
+    max_entries: Option<usize>,
+    /// Ignore the depth and entry limits
+    #[arg(long)]
+    unlimited: bool,
+    /// Walker threads: 0 = one per CPU, 1 = single-threaded
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Identify file types from their content
+    #[arg(long)]
+    detect_content: bool,
+    /// Unit for human-readable sizes
+    #[arg(long, value_enum)]
+    unit: Option<CliUnit>,
+    /// Print JSON instead of text (scan, top and dupes)
+    #[arg(long)]
+    json: bool,
+}
+
+impl CliScanArgs {
+    fn config(&self, mut config: AppConfig) -> AppConfig {
+        if let Some(filter) = &self.filter {
+            config.scan_filter = filter.clone();
+        }
+        config.exclude_patterns.extend(self.exclude.iter().cloned());
+        if let Some(depth) = self.max_depth {
+            config.max_depth = depth;
+        }
+        if let Some(entries) = self.max_entries {
+            config.max_entries = entries;
+        }
+        config.unlimited_scan |= self.unlimited;
+        if let Some(threads) = self.threads {
+            config.scan_threads = threads;
+        }
+        config.detect_content |= self.detect_content;
+        if let Some(unit) = self.unit {
+            config.unit = unit.into();
+        }
+        config
+    }
+
+    fn scan(&self, config: &AppConfig) -> Result<ScanResult, String> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None if !config.default_path.is_empty() => PathBuf::from(&config.default_path),
+            None => return Err("No folder given and no default path in Settings".into()),
+        };
+        if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        let options = config.scan_options().map_err(|e| format!("Invalid scan filter {}", e))?;
+
+        // Live counters only when a person is watching; scripts get clean output
+        let interactive = std::io::IsTerminal::is_terminal(&std::io::stderr());
+        let result = scan_directory(path, &options, &CancelToken::default(), |progress| {
+            if interactive {
+                eprint!(
+                    "\rScanning... {} files, {} dirs, {:.2} {}\x1b[K",
+                    progress.files_found,
+                    progress.dirs_found,
+                    config.unit.convert(progress.bytes_counted),
+                    config.unit
+                );
+            }
+        });
+        if interactive {
+            eprint!("\r\x1b[K");
+        }
+        result
+    }
+}
+
+fn print_json(out: &mut dyn Write, value: &impl serde::Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    writeln!(out, "{}", json).map_err(|e| e.to_string())
+}
+
+// `base` holds the saved settings that the options override (the defaults in the tests).
+// Results go to `out` (standard output outside the tests); progress and notes go to standard error
+pub(crate) fn run_cli(command: CliCommand, base: AppConfig, out: &mut dyn Write) -> Result<(), String> {
+    match command {
+        CliCommand::Scan { scan } => {
+            let config = scan.config(base);
+            let started = Instant::now();
+            let result = scan.scan(&config)?;
+            let files = result.files.iter().filter(|f| !f.is_dir).count();
+            let dirs = result.files.iter().filter(|f| f.is_dir).count();
+            let bytes: u64 = result.files.iter().filter(|f| !f.is_dir).map(|f| f.size).sum();
+            let elapsed = started.elapsed().as_secs_f64();
+
+            if scan.json {
+                return print_json(out, &serde_json::json!({
+                    "root": result.root,
+                    "files": files,
+                    "dirs": dirs,
+                    "bytes": bytes,
+                    "seconds": elapsed,
+                    "hit_depth_limit": result.hit_depth_limit,
+                    "hit_entry_limit": result.hit_entry_limit,
+                    "skipped_dirs": result.skipped_dirs,
+                }));
+            }
+            let unit = config.unit;
+            let mut report = format!(
+                "{}\n{} files, {} dirs, {:.2} {} ({:.1}s)\n",
+                result.root,
+                files,
+                dirs,
+                unit.convert(bytes),
+                unit,
+                elapsed
+            );
+            if result.hit_entry_limit {
+                report += &format!("Entry limit ({}) reached, results are incomplete\n", config.max_entries);
+            }
+            if result.hit_depth_limit {
+                report += &format!("Depth limit ({}) reached\n", config.max_depth);
+            }
+            if !result.skipped_dirs.is_empty() {
+                report += &format!("{} subtrees skipped\n", result.skipped_dirs.len());
+            }
+            write!(out, "{}", report).map_err(|e| e.to_string())
+        }
+
+        CliCommand::Top { scan, count, files_only, dirs_only } => {
+            let config = scan.config(base);
+            let result = scan.scan(&config)?;
+            let mut files: Vec<FileInfo> = result
+                .files
+                .into_iter()
+                .filter(|f| (!files_only || !f.is_dir) && (!dirs_only || f.is_dir))
+                .collect();
+            sort_files(&mut files, SortMethod::SizeLargest);
+            files.truncate(count);
+
+            if scan.json {
+                let records: Vec<ExportRecord> = files.iter().map(ExportRecord::new).collect();
+                return print_json(out, &records);
+            }
+            for file in &files {
+                writeln!(out, "{}", size_label(file, &file.path, config.unit)).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+
+        CliCommand::Dupes { scan } => {
+            let config = scan.config(base);
+            let result = scan.scan(&config)?;
+            let interactive = std::io::IsTerminal::is_terminal(&std::io::stderr());
+            let groups = find_duplicates(&result.files, &CancelToken::default(), |stage| {
+                if interactive {
+                    eprint!("\r{}\x1b[K", stage);
+                }
+            })?;
+            if interactive {
+                eprint!("\r\x1b[K");
+            }
+
+            if scan.json {
+                let groups: Vec<serde_json::Value> = groups
+                    .iter()
+                    .map(|g| {
+                        serde_json::json!({
+                            "size": g.size,
+                            "wasted": g.wasted(),
+                            "paths": g.files.iter().map(|f| &f.path).collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect();
+                return print_json(out, &groups);
+            }
+            let unit = config.unit;
+            let wasted: u64 = groups.iter().map(|g| g.wasted()).sum();
+            let mut report = String::new();
+            for group in &groups {
+                report += &format!(
+                    "{} copies of {:.2} {} ({:.2} {} wasted):\n",
+                    group.files.len(),
+                    unit.convert(group.size),
+                    unit,
+                    unit.convert(group.wasted()),
+                    unit
+                );
+                for file in &group.files {
+                    report += &format!("  {}\n", file.path);
+                }
+            }
+            report += &format!(
+                "{} duplicate groups, {:.2} {} wasted\n",
+                groups.len(),
+                unit.convert(wasted),
+                unit
+            );
+            write!(out, "{}", report).map_err(|e| e.to_string())
+        }
+
+        CliCommand::Export { scan, output, format, sort } => {
+            let config = scan.config(base);
+            let result = scan.scan(&config)?;
+            let mut files = result.files;
+            sort_files(&mut files, sort.map_or(config.default_sort, SortMethod::from));
+            let records: Vec<ExportRecord> = files.iter().map(ExportRecord::new).collect();
+
+            let format = match (format, &output) {
+                (Some(CliExportFormat::Csv), _) => ExportFormat::Csv,
+                (Some(CliExportFormat::Json), _) => ExportFormat::Json,
+                (None, Some(path)) => ExportFormat::from_path(path),
+                (None, None) => ExportFormat::Csv,
+            };
+            match output {
+                Some(path) => {
+                    export_results(&path, format, &records)?;
+                    eprintln!("Exported {} items to {}", records.len(), path.display());
+                }
+                None => write!(out, "{}", format_export(format, &records)?).map_err(|e| e.to_string())?,
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "tui")]
+        CliCommand::Tui { scan } => {
+            let config = scan.config(base);
+            let result = scan.scan(&config)?;
+            run_tui(config, result)
+        }
+    }
+}
+
+// The GUI build has no console on Windows; borrow the one of the shell that started us
+#[cfg(all(windows, feature = "gui"))]
+pub(crate) fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+
+    // Small tree: a.txt (10 bytes) and sub/b.bin (20 bytes)
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("disk_maid_cli_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), vec![b'a'; 10]).unwrap();
+        fs::write(dir.join("sub/b.bin"), vec![0u8; 20]).unwrap();
+        dir
+    }
+
+    // Runs against the default settings so the developer's saved ones cannot change the output
+    fn run(args: &[&str]) -> String {
+        let mut full = vec!["disk_maid"];
+        full.extend_from_slice(args);
+        let command = Cli::try_parse_from(full).unwrap().command.unwrap();
+        let mut out = Vec::new();
+        run_cli(command, AppConfig::default(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn command_definition_is_consistent() {
+        <Cli as clap::CommandFactory>::command().debug_assert();
+    }
+
+    #[test]
+    fn no_subcommand_means_the_gui() {
+        assert!(Cli::try_parse_from(["disk_maid"]).unwrap().command.is_none());
+    }
+
+    #[test]
+    fn subcommands_parse_their_options() {
+        let cli = Cli::try_parse_from(["disk_maid", "top", "/data", "-n", "5", "--files-only", "--json"]).unwrap();
+        let Some(CliCommand::Top { scan, count, files_only, dirs_only }) = cli.command else {
+            panic!("not a top command");
+        };
+        assert_eq!((count, files_only, dirs_only), (5, true, false));
+        assert_eq!(scan.path, Some(PathBuf::from("/data")));
+        assert!(scan.json);
+
+        let cli = Cli::try_parse_from([
+            "disk_maid", "export", "-o", "out.json", "--format", "csv", "--sort", "size-largest", "--unit", "gb",
+            "--exclude", "node_modules", "--exclude", "/proc",
+        ])
+        .unwrap();
+        let Some(CliCommand::Export { scan, output, format, sort }) = cli.command else {
+            panic!("not an export command");
+        };
+        assert_eq!(output, Some(PathBuf::from("out.json")));
+        assert!(matches!(format, Some(CliExportFormat::Csv)));
+        assert_eq!(sort.map(SortMethod::from), Some(SortMethod::SizeLargest));
+        assert_eq!(scan.unit.map(Unit::from), Some(Unit::GB));
+        assert_eq!(scan.exclude, vec!["node_modules", "/proc"]);
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        assert!(Cli::try_parse_from(["disk_maid", "top", "--files-only", "--dirs-only"]).is_err());
+        assert!(Cli::try_parse_from(["disk_maid", "export", "--format", "xml"]).is_err());
+        assert!(Cli::try_parse_from(["disk_maid", "top", "-n", "many"]).is_err());
+        assert!(Cli::try_parse_from(["disk_maid", "clean"]).is_err());
+    }
+
+    #[test]
+    fn scan_json_reports_the_totals() {
+        let dir = fixture("scan_json");
+        let output = run(&["scan", &dir.to_string_lossy(), "--json"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["root"], dir.to_string_lossy().as_ref());
+        assert_eq!((value["files"].as_u64(), value["dirs"].as_u64(), value["bytes"].as_u64()), (Some(2), Some(1), Some(30)));
+        assert_eq!(value["hit_entry_limit"], false);
+        assert_eq!(value["skipped_dirs"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn export_writes_csv_to_standard_output() {
+        let dir = fixture("export_csv");
+        // Makes sub bigger than b.bin, so no two rows tie and the walk order cannot show through
+        fs::write(dir.join("sub/c.bin"), vec![0u8; 5]).unwrap();
+        let output = run(&["export", &dir.to_string_lossy(), "--sort", "size-largest"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let rows: Vec<Vec<&str>> = output.lines().map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows[0], vec!["path", "size", "type", "modified", "is_dir"]);
+        let summary: Vec<(String, &str, &str)> = rows[1..]
+            .iter()
+            .map(|row| (row[0].strip_prefix(dir.to_string_lossy().as_ref()).unwrap().to_string(), row[1], row[4]))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/sub".to_string(), "25", "true"),
+                ("/sub/b.bin".to_string(), "20", "false"),
+                ("/a.txt".to_string(), "10", "false"),
+                ("/sub/c.bin".to_string(), "5", "false"),
+            ]
+        );
+    }
+
+    #[test]
+    fn export_picks_json_from_the_output_extension() {
+        let dir = fixture("export_json");
+        let target = dir.with_extension("json");
+        let output = run(&["export", &dir.to_string_lossy(), "-o", &target.to_string_lossy()]);
+        let written = fs::read_to_string(&target).unwrap();
+        fs::remove_file(&target).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(output.is_empty());
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        let mut sizes: Vec<(bool, u64)> = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| (record["is_dir"].as_bool().unwrap(), record["size"].as_u64().unwrap()))
+            .collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![(false, 10), (false, 20), (true, 20)]);
+    }
+}
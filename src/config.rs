@@ -0,0 +1,174 @@
+// User settings (saved as JSON in the disk-maid-rs config directory) and how they turn into scan options
+
+use crate::actions::TRASH_SUPPORTED;
+use crate::model::{ResultFilter, SortMethod};
+use crate::scanner::{ExcludeSet, FilterSet, ScanLimits, ScanOptions};
+use std::{fs, path::PathBuf};
+
+// --- CONFIGURATION STRUCTS ---
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AppConfig {
+    pub scan_filter: String,
+    pub unit: Unit,
+    #[serde(default)]
+    pub default_path: String,
+    // NEW: Save the default sort method
+    #[serde(default)]
+    pub default_sort: SortMethod,
+    // Scan limits, ignored when `unlimited_scan` is set
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    #[serde(default)]
+    pub unlimited_scan: bool,
+    // Worker threads for the directory walker: 0 = one per CPU, 1 = single-threaded
+    #[serde(default)]
+    pub scan_threads: usize,
+    // Paths or names the walker never enters, e.g. "/proc" or "node_modules"
+    #[serde(default = "default_exclude_patterns")]
+    pub exclude_patterns: Vec<String>,
+    // Skip the Trash and remove files for good
+    #[serde(default = "default_permanent_delete")]
+    pub permanent_delete: bool,
+    // Identify file types from their first bytes after each scan
+    #[serde(default)]
+    pub detect_content: bool,
+}
+
+fn default_max_depth() -> usize {
+    32
+}
+
+fn default_max_entries() -> usize {
+    200_000
+}
+
+fn default_permanent_delete() -> bool {
+    !TRASH_SUPPORTED
+}
+
+pub fn default_exclude_patterns() -> Vec<String> {
+    let defaults: &[&str] = if cfg!(target_os = "linux") {
+        // Pseudo-filesystems: huge, volatile and never worth cleaning
+        &["/proc", "/sys", "/dev", "/run"]
+    } else if cfg!(windows) {
+        &["$Recycle.Bin", "System Volume Information"]
+    } else {
+        &[]
+    };
+    defaults.iter().map(|p| p.to_string()).collect()
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            scan_filter: "*".to_string(),
+            unit: Unit::MB,
+            default_path: String::new(),
+            default_sort: SortMethod::NameAZ,
+            max_depth: default_max_depth(),
+            max_entries: default_max_entries(),
+            unlimited_scan: false,
+            scan_threads: 0,
+            exclude_patterns: default_exclude_patterns(),
+            permanent_delete: default_permanent_delete(),
+            detect_content: false,
+        }
+    }
+}
+
+impl AppConfig {
+    // Without a supported Trash every delete is permanent, whatever the setting says
+    pub fn deletes_permanently(&self) -> bool {
+        self.permanent_delete || !TRASH_SUPPORTED
+    }
+
+    pub fn scan_options(&self) -> Result<ScanOptions, String> {
+        let limits = if self.unlimited_scan {
+            ScanLimits::default()
+        } else {
+            ScanLimits {
+                max_depth: Some(self.max_depth),
+                max_entries: Some(self.max_entries),
+            }
+        };
+        Ok(ScanOptions {
+            filter: FilterSet::parse(&self.scan_filter)?,
+            exclude: ExcludeSet::new(&self.exclude_patterns)?,
+            entry_filter: ResultFilter::default(),
+            limits,
+            threads: self.scan_threads,
+            detect_content: self.detect_content,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Unit {
+    KB,
+    MB,
+    GB,
+}
+
+impl Unit {
+    fn bytes_per_unit(&self) -> f64 {
+        match self {
+            Unit::KB => 1024.0,
+            Unit::MB => 1024.0 * 1024.0,
+            Unit::GB => 1024.0 * 1024.0 * 1024.0,
+        }
+    }
+
+    pub fn convert(&self, bytes: u64) -> f64 {
+        bytes as f64 / self.bytes_per_unit()
+    }
+
+    pub fn to_bytes(self, value: f64) -> u64 {
+        (value * self.bytes_per_unit()) as u64
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Unit::KB => "KB",
+                Unit::MB => "MB",
+                Unit::GB => "GB",
+            }
+        )
+    }
+}
+
+// --- SETTINGS FILE ---
+
+fn get_config_path() -> Result<PathBuf, anyhow::Error> {
+    let config_dir = dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
+    let app_dir = config_dir.join("disk-maid-rs");
+    if !app_dir.exists() {
+        fs::create_dir_all(&app_dir)?;
+    }
+    Ok(app_dir.join("settings.json"))
+}
+
+pub fn load_config() -> Result<AppConfig, anyhow::Error> {
+    let path = get_config_path()?;
+    if path.exists() {
+        let content = fs::read_to_string(path)?;
+        let config: AppConfig = serde_json::from_str(&content)?;
+        Ok(config)
+    } else {
+        Ok(AppConfig::default())
+    }
+}
+
+pub fn save_config(config: &AppConfig) -> Result<(), anyhow::Error> {
+    let path = get_config_path()?;
+    let content = serde_json::to_string_pretty(config)?;
+    fs::write(path, content)?;
+    Ok(())
+}
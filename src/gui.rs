@@ -0,0 +1,3455 @@
+// The iced desktop application
+
+use iced::futures::channel::{mpsc, oneshot};
+use iced::widget::{
+    button, canvas, checkbox, column, container, pick_list, progress_bar, row, scrollable, text, text_input,
+    vertical_space,
+};
+use iced::{executor, mouse, Alignment, Application, Color, Command, Element, Length, Point, Rectangle, Size, Theme};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use disk_maid::actions::{
+    delete_permanently, export_results, format_timestamp, move_to_trash, run_batch, undo_journal_entry, BatchAction,
+    BatchReport, DeleteReport, ExportFormat, ExportRecord, JournalEntry, JournalItem, RestoreOutcome, Reversible,
+    TrashedItem, TRASH_SUPPORTED,
+};
+use disk_maid::config::{default_exclude_patterns, load_config, save_config, AppConfig, Unit};
+use disk_maid::model::{
+    size_label, sort_files, type_breakdown, EntryKind, FileCategory, FileInfo, FilterInputs, ResultFilter, ScanResult,
+    ScanTree, SortMethod, TypeBreakdown, TypeFilter, TypeStats,
+};
+use disk_maid::scanner::{
+    adjust_ancestor_totals, compile_pattern, find_duplicates, scan_directory, CancelToken, DuplicateGroup,
+    FilterSet, ScanProgress,
+};
+use disk_maid::snapshot::{
+    compare_snapshots, load_snapshot, load_snapshot_index, remove_snapshot, save_snapshot, ChangeKind, PathDelta,
+    ScanComparison, Snapshot, SnapshotSummary, MAX_SNAPSHOTS,
+};
+
+// --- CUSTOM THEME & STYLING ---
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ContainerStyle {
+    #[default]
+    Base,
+    RowOdd,
+    RowEven,
+    RowFocused,
+}
+
+impl From<ContainerStyle> for iced::theme::Container {
+    fn from(style: ContainerStyle) -> Self {
+        iced::theme::Container::Custom(Box::new(move |_theme: &Theme| {
+            match style {
+                ContainerStyle::Base => container::Appearance {
+                    background: Some(iced::Background::Color(Color::from_rgb8(10, 40, 70))),
+                    border: iced::Border {
+                        radius: 5.0.into(),
+                        width: 1.0,
+                        color: Color::from_rgb8(0, 100, 150),
+                    },
+                    text_color: Some(Color::from_rgb8(255, 255, 255)),
+                    shadow: iced::Shadow::default(),
+                },
+                ContainerStyle::RowOdd => container::Appearance {
+                    background: Some(iced::Background::Color(Color::from_rgb8(45, 45, 45))),
+                    text_color: Some(Color::from_rgb8(255, 255, 255)),
+                    ..Default::default()
+                },
+                ContainerStyle::RowFocused => container::Appearance {
+                    background: Some(iced::Background::Color(Color::from_rgb8(20, 70, 110))),
+                    text_color: Some(Color::from_rgb8(255, 255, 255)),
+                    ..Default::default()
+                },
+                ContainerStyle::RowEven => container::Appearance {
+                    background: Some(iced::Background::Color(Color::from_rgb8(30, 30, 30))),
+                    text_color: Some(Color::from_rgb8(255, 255, 255)),
+                    ..Default::default()
+                },
+            }
+        }))
+    }
+}
+
+// --- APPLICATION ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    MainMenu,
+    FileScan,
+    RecentScans,
+    Settings,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct DiskViz {
+    current_screen: Screen,
+    config: AppConfig,
+    status_message: String,
+    
+    // Buffers for Settings Screen
+    scan_filter_buffer: String,
+    default_path_buffer: String, 
+    selected_unit: Unit,
+    settings_default_sort: SortMethod, // NEW: Buffer for sorting choice in settings
+    max_depth_buffer: String,
+    max_entries_buffer: String,
+    unlimited_scan_buffer: bool,
+    scan_threads_buffer: String,
+    exclude_buffer: Vec<String>,
+    exclude_input: String,
+    permanent_delete_buffer: bool,
+    detect_content_buffer: bool,
+
+    is_scanning: bool,
+    scanned_files: Vec<FileInfo>,
+    scan_tree: Option<ScanTree>,
+    // Directory currently shown in the tree view
+    tree_cursor: usize,
+    // Entry picked in the sunburst; the list scrolls to it and highlights it
+    focused_entry: Option<String>,
+    result_view: ResultView,
+    list_scroll: ListScroll,
+    filter_inputs: FilterInputs,
+    result_filter: ResultFilter,
+    filter_error: Option<String>,
+    // Also apply the result filter while walking the next scan
+    bake_filters: bool,
+    // Kept up to date with `scanned_files` so the view does not regroup on every frame
+    type_breakdown: TypeBreakdown,
+    duplicates: Option<Vec<DuplicateGroup>>,
+    duplicate_search: Option<CancelToken>,
+    // Bumped for every duplicate search so a cancelled one cannot touch its successor
+    duplicate_generation: u64,
+    duplicate_progress: String,
+    scan_path_buffer: String,
+    scan_progress: ScanProgress,
+    skipped_dirs: Vec<String>,
+    // Bumped on every start/stop so messages from an abandoned scan can be ignored
+    scan_generation: u64,
+    scan_cancel: Option<CancelToken>,
+    pending_delete_file: Option<String>,
+    // Paths ticked in the result lists, kept across view switches
+    selected: HashSet<String>,
+    // How many selection roots there are and their total size, kept in step with `selected`
+    // and the results so the view does not walk the selection every frame
+    selection_root_count: usize,
+    selection_bytes: u64,
+    select_pattern: String,
+    // Batch waiting for the consolidated confirmation
+    pending_batch: Option<BatchAction>,
+    batch_running: bool,
+    // Trash and move steps of this session that can still be undone, newest last
+    journal: Vec<JournalEntry>,
+    undo_running: bool,
+    // Saved scans, newest first
+    recent_scans: Vec<SnapshotSummary>,
+    // Snapshot being browsed instead of a live scan; nothing on disk is touched while set
+    snapshot_view: Option<SnapshotSummary>,
+    // Up to two snapshots ticked for comparison
+    compare_picks: Vec<SnapshotSummary>,
+    comparison: Option<ScanComparison>,
+    comparison_kind: EntryKind,
+    current_sort: SortMethod,
+}
+
+// Last known scroll position of the result list, used to pick which rows to build
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListScroll {
+    offset: f32,
+    height: f32,
+}
+
+impl Default for ListScroll {
+    fn default() -> Self {
+        ListScroll {
+            offset: 0.0,
+            height: LIST_HEIGHT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultView {
+    #[default]
+    List,
+    Tree,
+    Treemap,
+    Sunburst,
+    Types,
+    Duplicates,
+}
+
+impl ResultView {
+    const ALL: [ResultView; 6] = [
+        ResultView::List,
+        ResultView::Tree,
+        ResultView::Treemap,
+        ResultView::Sunburst,
+        ResultView::Types,
+        ResultView::Duplicates,
+    ];
+}
+
+impl std::fmt::Display for ResultView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ResultView::List => "Flat List",
+                ResultView::Tree => "Folder Tree",
+                ResultView::Treemap => "Treemap",
+                ResultView::Sunburst => "Sunburst",
+                ResultView::Types => "File Types",
+                ResultView::Duplicates => "Duplicates",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ScreenChanged(Screen),
+    StartScanPressed,
+    StopScanPressed,
+    BackToMainMenu,
+    ExitApp,
+    ScanPathChanged(String),
+    ScanProgress(u64, ScanProgress),
+    // The result comes with its snapshot, copied off the UI thread; none for an empty scan
+    ScanCompleted(u64, Result<(ScanResult, Option<Box<Snapshot>>), String>),
+    ScanFilterChanged(String),
+    UnitChanged(Unit),
+    SaveSettingsPressed,
+    ConfigSaved(Result<(), String>),
+    RequestDelete(String),
+    ConfirmDelete,
+    CancelDelete,
+    // Tagged with the scan generation the deleted entry was shown in
+    FileDeleted(u64, DeleteReport),
+    FileTrashed(u64, String, Result<TrashedItem, String>),
+    OpenFolder(String),
+    BrowseScanPathPressed,
+    ScanPathSelected(Option<String>),
+    BrowseDefaultPathPressed,
+    DefaultPathSelected(Option<String>),
+    DefaultPathChanged(String),
+    SortChanged(SortMethod), 
+    // NEW: Update the buffer in Settings screen
+    SettingsDefaultSortChanged(SortMethod), 
+    ResultViewChanged(ResultView),
+    FilterMinSizeChanged(String),
+    FilterMaxSizeChanged(String),
+    FilterModifiedAfterChanged(String),
+    FilterModifiedBeforeChanged(String),
+    FilterKindChanged(EntryKind),
+    FilterTypeChanged(TypeFilter),
+    BakeFiltersToggled(bool),
+    ClearFiltersPressed,
+    FindDuplicatesPressed,
+    CancelDuplicatesPressed,
+    DuplicatesProgress(u64, String),
+    DuplicatesFound(u64, Result<Vec<DuplicateGroup>, String>),
+    TreeOpen(usize),
+    FocusEntry(usize),
+    MaxDepthChanged(String),
+    MaxEntriesChanged(String),
+    UnlimitedScanToggled(bool),
+    ScanThreadsChanged(String),
+    DetectContentToggled(bool),
+    ExcludeInputChanged(String),
+    AddExcludePressed,
+    RemoveExclude(usize),
+    ResetExcludesPressed,
+    PermanentDeleteToggled(bool),
+    ToggleSelected(String, bool),
+    SelectAllVisiblePressed,
+    SelectPatternChanged(String),
+    SelectMatchingPressed,
+    SelectDuplicateCopiesPressed,
+    ClearSelectionPressed,
+    CopySelectedPathsPressed,
+    RequestBatch(BatchAction),
+    BatchMovePressed,
+    BatchMoveDestination(Option<String>),
+    ExportPressed(ExportFormat),
+    ExportDestination(ExportFormat, Option<String>),
+    ExportCompleted(String, usize, Result<(), String>),
+    SnapshotSaved(Result<Vec<SnapshotSummary>, String>),
+    OpenSnapshot(SnapshotSummary),
+    SnapshotLoaded(Result<Snapshot, String>),
+    RemoveSnapshot(SnapshotSummary),
+    SnapshotRemoved(Result<Vec<SnapshotSummary>, String>),
+    ToggleComparePick(SnapshotSummary, bool),
+    ComparePressed,
+    ComparisonReady(Result<ScanComparison, String>),
+    ComparisonKindChanged(EntryKind),
+    CloseComparison,
+    ConfirmBatch,
+    CancelBatch,
+    BatchCompleted(u64, BatchAction, BatchReport),
+    UndoPressed,
+    ResultsScrolled(scrollable::Viewport),
+    UndoCompleted(JournalEntry, Vec<RestoreOutcome>),
+    BackgroundTaskLost,
+}
+
+// Runs slow disk work on its own thread, like the scan, so the executor keeps serving the UI
+fn run_in_background<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+    to_message: impl FnOnce(T) -> Message + Send + 'static,
+) -> Command<Message> {
+    let (sender, receiver) = oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    Command::perform(async move { receiver.await.ok() }, move |outcome| match outcome {
+        Some(value) => to_message(value),
+        None => Message::BackgroundTaskLost,
+    })
+}
+
+impl DiskViz {
+    // Drop removed entries, and everything below them, from every view of the results.
+    // Returns what was dropped under each of `paths` so an undo can put it back.
+    fn remove_from_results(&mut self, paths: &[String]) -> HashMap<String, Vec<FileInfo>> {
+        let roots: HashSet<&str> = paths.iter().map(String::as_str).collect();
+        let mut dropped: HashMap<String, Vec<FileInfo>> = HashMap::new();
+        for file in &self.scanned_files {
+            // Filed under the outermost removed path above it
+            let root = Path::new(&file.path)
+                .ancestors()
+                .map(|a| a.to_string_lossy())
+                .filter(|a| roots.contains(a.as_ref()))
+                .last();
+            if let Some(root) = root {
+                dropped.entry(root.to_string()).or_default().push(file.clone());
+            }
+        }
+        let removed: HashSet<String> = dropped.values().flatten().map(|f| f.path.clone()).collect();
+        self.remove_paths_from_results(&removed);
+        dropped
+    }
+
+    // Put restored entries back in a single pass, as long as they belong to the scan currently shown
+    fn restore_to_results(&mut self, files: Vec<FileInfo>) {
+        let Some(tree) = &self.scan_tree else {
+            return;
+        };
+        let root = PathBuf::from(&tree.node(ScanTree::ROOT).info.path);
+        let present: HashSet<&str> = self.scanned_files.iter().map(|f| f.path.as_str()).collect();
+        let restored: Vec<FileInfo> = files
+            .into_iter()
+            .filter(|f| Path::new(&f.path).starts_with(&root) && !present.contains(f.path.as_str()))
+            .collect();
+        if restored.is_empty() {
+            return;
+        }
+        adjust_ancestor_totals(&mut self.scanned_files, &restored, false);
+        self.scanned_files.extend(restored);
+        sort_files(&mut self.scanned_files, self.current_sort);
+        self.rebuild_tree();
+        self.type_breakdown = type_breakdown(&self.scanned_files);
+        self.refresh_selection_summary();
+    }
+
+    fn remove_paths_from_results(&mut self, removed: &HashSet<String>) {
+        if removed.is_empty() {
+            return;
+        }
+        // Take the removed entries out of the du-style totals of whatever is left
+        let dropped: Vec<FileInfo> = self.scanned_files.iter().filter(|f| removed.contains(&f.path)).cloned().collect();
+        adjust_ancestor_totals(&mut self.scanned_files, &dropped, true);
+        self.scanned_files.retain(|f| !removed.contains(&f.path));
+        self.selected.retain(|p| !removed.contains(p));
+        self.rebuild_tree();
+        self.type_breakdown = type_breakdown(&self.scanned_files);
+        self.refresh_selection_summary();
+
+        if let Some(groups) = &mut self.duplicates {
+            for group in groups.iter_mut() {
+                group.files.retain(|f| !removed.contains(&f.path));
+            }
+            groups.retain(|g| g.files.len() > 1);
+        }
+    }
+
+    // Jump the result list back to the top once the rows underneath it are different ones
+    fn scroll_results_to_top(&mut self) -> Command<Message> {
+        self.list_scroll.offset = 0.0;
+        scrollable::snap_to(results_scroll_id(), scrollable::RelativeOffset::START)
+    }
+
+    // Forgets the current results and everything tied to them
+    fn clear_results(&mut self) {
+        self.scanned_files.clear();
+        if let Some(search) = self.duplicate_search.take() {
+            search.cancel();
+        }
+        self.duplicates = None;
+        self.scan_tree = None;
+        self.tree_cursor = ScanTree::ROOT;
+        self.skipped_dirs.clear();
+        self.pending_delete_file = None;
+        self.selected.clear();
+        self.refresh_selection_summary();
+        self.pending_batch = None;
+        self.focused_entry = None;
+        self.snapshot_view = None;
+    }
+
+    // Shows a finished scan or an opened snapshot
+    fn show_results(&mut self, root: &str, mut files: Vec<FileInfo>, skipped_dirs: Vec<String>) -> Command<Message> {
+        sort_files(&mut files, self.current_sort);
+        self.scan_tree = Some(ScanTree::build(root, &files));
+        self.tree_cursor = ScanTree::ROOT;
+        self.type_breakdown = type_breakdown(&files);
+        self.scanned_files = files;
+        self.skipped_dirs = skipped_dirs;
+        self.refresh_selection_summary();
+        self.scroll_results_to_top()
+    }
+
+    fn rebuild_tree(&mut self) {
+        if let Some(tree) = &self.scan_tree {
+            let root = tree.node(ScanTree::ROOT).info.path.clone();
+            let cursor = tree.node(self.tree_cursor).info.path.clone();
+            let tree = ScanTree::build(&root, &self.scanned_files);
+            // Stay in the same folder, or the closest one that still exists
+            self.tree_cursor = Path::new(&cursor)
+                .ancestors()
+                .find_map(|dir| tree.find(dir.to_string_lossy().as_ref()))
+                .unwrap_or(ScanTree::ROOT);
+            self.scan_tree = Some(tree);
+        }
+    }
+
+    // Entries the current view lists, in display order
+    fn visible_entries(&self) -> Vec<&FileInfo> {
+        match (self.result_view, &self.scan_tree, &self.duplicates) {
+            (ResultView::Tree | ResultView::Treemap | ResultView::Sunburst, Some(tree), _) => tree
+                .sorted_children(self.tree_cursor, self.current_sort)
+                .into_iter()
+                .map(|id| &tree.node(id).info)
+                .filter(|info| self.result_filter.matches(info))
+                .collect(),
+            (ResultView::Duplicates, _, Some(groups)) => {
+                groups.iter().flat_map(|g| g.files.iter()).collect()
+            }
+            (ResultView::Duplicates, _, None) | (ResultView::Types, _, _) => Vec::new(),
+            _ => self
+                .scanned_files
+                .iter()
+                .filter(|f| self.result_filter.matches(f))
+                .collect(),
+        }
+    }
+
+    // Selected entries without a selected ancestor, so nothing is acted on or counted twice
+    fn selection_roots(&self) -> Vec<&FileInfo> {
+        self.scanned_files
+            .iter()
+            .filter(|f| {
+                self.selected.contains(&f.path)
+                    && !Path::new(&f.path)
+                        .ancestors()
+                        .skip(1)
+                        .any(|a| self.selected.contains(a.to_string_lossy().as_ref()))
+            })
+            .collect()
+    }
+
+    fn refresh_selection_summary(&mut self) {
+        let roots = self.selection_roots();
+        let (count, bytes) = (roots.len(), roots.iter().map(|f| f.size).sum());
+        self.selection_root_count = count;
+        self.selection_bytes = bytes;
+    }
+
+    fn refresh_result_filter(&mut self) {
+        match self.filter_inputs.parse(self.config.unit) {
+            Ok(filter) => {
+                self.result_filter = filter;
+                self.filter_error = None;
+            }
+            Err(e) => self.filter_error = Some(e),
+        }
+    }
+}
+
+impl Application for DiskViz {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        let config = load_config().unwrap_or_default();
+        
+        let home_dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .to_string_lossy()
+            .to_string();
+
+        let initial_path = if config.default_path.is_empty() {
+            home_dir.clone()
+        } else {
+            config.default_path.clone()
+        };
+
+        (
+            DiskViz {
+                current_screen: Screen::MainMenu,
+                // Initialize Settings buffers
+                scan_filter_buffer: config.scan_filter.clone(),
+                default_path_buffer: config.default_path.clone(),
+                selected_unit: config.unit,
+                settings_default_sort: config.default_sort, // Load default sort to buffer
+                max_depth_buffer: config.max_depth.to_string(),
+                max_entries_buffer: config.max_entries.to_string(),
+                unlimited_scan_buffer: config.unlimited_scan,
+                scan_threads_buffer: config.scan_threads.to_string(),
+                exclude_buffer: config.exclude_patterns.clone(),
+                exclude_input: String::new(),
+                permanent_delete_buffer: config.permanent_delete,
+                detect_content_buffer: config.detect_content,
+
+                config: config.clone(),
+                is_scanning: false,
+                status_message: format!("Welcome! Ready to scan: {}", initial_path),
+                scanned_files: Vec::new(),
+                scan_tree: None,
+                tree_cursor: ScanTree::ROOT,
+                focused_entry: None,
+                result_view: ResultView::default(),
+                list_scroll: ListScroll::default(),
+                filter_inputs: FilterInputs::default(),
+                result_filter: ResultFilter::default(),
+                filter_error: None,
+                bake_filters: false,
+                type_breakdown: TypeBreakdown::default(),
+                duplicates: None,
+                duplicate_search: None,
+                duplicate_generation: 0,
+                duplicate_progress: String::new(),
+                scan_path_buffer: initial_path,
+                scan_progress: ScanProgress::default(),
+                skipped_dirs: Vec::new(),
+                scan_generation: 0,
+                scan_cancel: None,
+                pending_delete_file: None,
+                selected: HashSet::new(),
+                selection_root_count: 0,
+                selection_bytes: 0,
+                select_pattern: String::new(),
+                pending_batch: None,
+                batch_running: false,
+                journal: Vec::new(),
+                undo_running: false,
+                recent_scans: load_snapshot_index().unwrap_or_default(),
+                snapshot_view: None,
+                compare_picks: Vec::new(),
+                comparison: None,
+                comparison_kind: EntryKind::All,
+                current_sort: config.default_sort, // Apply default sort on startup
+            },
+            Command::none(),
+        )
+    }
+
+    fn title(&self) -> String {
+        "Disk Maid".to_string()
+    }
+
+    fn theme(&self) -> Theme {
+        Theme::Dark
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::ScreenChanged(screen) => {
+                self.current_screen = screen;
+                self.pending_delete_file = None;
+                // The comparison and the scan results share the list scroll position
+                self.scroll_results_to_top()
+            }
+
+            Message::BackToMainMenu => {
+                self.current_screen = Screen::MainMenu;
+                self.status_message = "Welcome to Disk Maid!".into();
+                Command::none()
+            }
+
+            Message::ExitApp => {
+                std::process::exit(0);
+            }
+
+            Message::BrowseScanPathPressed => {
+                Command::perform(
+                    async {
+                        let handle = rfd::AsyncFileDialog::new()
+                            .set_title("Select Directory to Scan")
+                            .pick_folder()
+                            .await;
+                        handle.map(|h| h.path().to_string_lossy().to_string())
+                    },
+                    Message::ScanPathSelected
+                )
+            }
+
+            Message::ScanPathSelected(Some(path)) => {
+                self.scan_path_buffer = path;
+                Command::none()
+            }
+
+            Message::ScanPathSelected(None) => Command::none(),
+
+            Message::BrowseDefaultPathPressed => {
+                Command::perform(
+                    async {
+                        let handle = rfd::AsyncFileDialog::new()
+                            .set_title("Select Default Directory")
+                            .pick_folder()
+                            .await;
+                        handle.map(|h| h.path().to_string_lossy().to_string())
+                    },
+                    Message::DefaultPathSelected
+                )
+            }
+
+            Message::DefaultPathSelected(Some(path)) => {
+                self.default_path_buffer = path;
+                Command::none()
+            }
+            
+            Message::DefaultPathSelected(None) => Command::none(),
+
+            Message::DefaultPathChanged(val) => {
+                self.default_path_buffer = val;
+                Command::none()
+            }
+
+            Message::StartScanPressed => {
+                let path = PathBuf::from(self.scan_path_buffer.clone());
+                if !path.exists() {
+                    self.status_message = "Error: Path does not exist!".into();
+                    return Command::none();
+                }
+                if !path.is_dir() {
+                    self.status_message = "Error: Path is not a directory!".into();
+                    return Command::none();
+                }
+
+                if self.bake_filters && self.filter_error.is_some() {
+                    self.status_message = "Error: Fix the result filters before baking them into the scan".into();
+                    return Command::none();
+                }
+                let options = match self.config.scan_options() {
+                    Ok(mut options) => {
+                        if self.bake_filters {
+                            options.entry_filter = self.result_filter.clone();
+                        }
+                        options
+                    }
+                    Err(e) => {
+                        self.status_message = format!("Error: Invalid scan filter {}", e);
+                        return Command::none();
+                    }
+                };
+                let limits = options.limits;
+
+                if let Some(cancel) = self.scan_cancel.take() {
+                    cancel.cancel();
+                }
+                self.scan_generation += 1;
+                let generation = self.scan_generation;
+                let cancel = CancelToken::default();
+                self.scan_cancel = Some(cancel.clone());
+
+                self.is_scanning = true;
+                self.status_message = match (limits.max_depth, limits.max_entries) {
+                    (Some(depth), Some(entries)) => {
+                        format!("Scanning... (max depth {}, max {} entries)", depth, entries)
+                    }
+                    _ => "Scanning... (unlimited)".into(),
+                };
+                self.clear_results();
+                self.scan_progress = ScanProgress::default();
+
+                // Walk the disk on its own thread and stream progress batches back as messages
+                let (sender, receiver) = mpsc::unbounded();
+                std::thread::spawn(move || {
+                    let progress_sender = sender.clone();
+                    let result = scan_directory(path, &options, &cancel, |progress| {
+                        let _ = progress_sender
+                            .unbounded_send(Message::ScanProgress(generation, progress.clone()));
+                    })
+                    .map(|result| {
+                        let snapshot = (!result.files.is_empty()).then(|| Box::new(Snapshot::new(&result)));
+                        (result, snapshot)
+                    });
+                    let _ = sender.unbounded_send(Message::ScanCompleted(generation, result));
+                });
+
+                Command::run(receiver, |message| message)
+            }
+
+            Message::StopScanPressed => {
+                if let Some(cancel) = self.scan_cancel.take() {
+                    cancel.cancel();
+                }
+                self.scan_generation += 1;
+                self.is_scanning = false;
+                self.status_message = "Scan stopped.".into();
+                Command::none()
+            }
+
+            Message::ScanPathChanged(path) => {
+                self.scan_path_buffer = path;
+                Command::none()
+            }
+
+            // Drop anything still in flight from a scan that was stopped or replaced
+            Message::ScanProgress(generation, _) | Message::ScanCompleted(generation, _)
+                if generation != self.scan_generation =>
+            {
+                Command::none()
+            }
+
+            Message::ScanProgress(_, progress) => {
+                self.scan_progress = progress;
+                Command::none()
+            }
+
+            Message::ScanCompleted(_, Ok((result, snapshot))) => {
+                self.is_scanning = false;
+                self.scan_cancel = None;
+
+                // Saved in the background so the results can be reopened without rescanning
+                let save = match snapshot {
+                    Some(mut snapshot) => {
+                        run_in_background(move || save_snapshot(&mut snapshot), Message::SnapshotSaved)
+                    }
+                    None => Command::none(),
+                };
+
+                let file_count = result.files.iter().filter(|f| !f.is_dir).count();
+                let dir_count = result.files.iter().filter(|f| f.is_dir).count();
+                let total_size: u64 = result.files.iter().filter(|f| !f.is_dir).map(|f| f.size).sum();
+                self.status_message = format!(
+                    "Scan complete! {} files, {} dirs. Size: {:.2} {} ({:.1}s)",
+                    file_count,
+                    dir_count,
+                    self.config.unit.convert(total_size),
+                    self.config.unit,
+                    self.scan_progress.elapsed.as_secs_f64()
+                );
+                if result.hit_entry_limit {
+                    self.status_message.push_str(&format!(
+                        " | Entry limit ({}) reached, results are incomplete",
+                        self.config.max_entries
+                    ));
+                }
+                if result.hit_depth_limit {
+                    self.status_message.push_str(&format!(
+                        " | Depth limit ({}) reached",
+                        self.config.max_depth
+                    ));
+                }
+                if !result.skipped_dirs.is_empty() {
+                    self.status_message.push_str(&format!(
+                        ", {} subtrees skipped",
+                        result.skipped_dirs.len()
+                    ));
+                }
+                let scroll = self.show_results(&result.root, result.files, result.skipped_dirs);
+                Command::batch([scroll, save])
+            }
+
+            Message::SnapshotSaved(Ok(index)) => {
+                self.recent_scans = index;
+                Command::none()
+            }
+
+            Message::SnapshotSaved(Err(e)) | Message::SnapshotRemoved(Err(e)) => {
+                self.status_message = format!("Error: {}", e);
+                Command::none()
+            }
+
+            Message::OpenSnapshot(summary) => {
+                if self.is_scanning {
+                    self.status_message = "Stop the running scan before opening a snapshot.".into();
+                    return Command::none();
+                }
+                self.status_message = format!("Opening snapshot of {}...", summary.root);
+                run_in_background(move || load_snapshot(&summary), Message::SnapshotLoaded)
+            }
+
+            Message::SnapshotLoaded(Ok(snapshot)) => {
+                // A scan started while the snapshot was loading wins
+                if self.is_scanning {
+                    return Command::none();
+                }
+                // Anything still in flight for the previous results must not touch these
+                self.scan_generation += 1;
+                self.clear_results();
+                let summary = snapshot.summary;
+                self.status_message = format!(
+                    "Viewing the scan of {} from {} ({} files, {:.2} {}). Read-only: rescan to make changes.",
+                    summary.root,
+                    format_timestamp(summary.created),
+                    summary.files,
+                    self.config.unit.convert(summary.bytes),
+                    self.config.unit
+                );
+                self.scan_path_buffer = summary.root.clone();
+                self.current_screen = Screen::FileScan;
+                let scroll = self.show_results(&summary.root, snapshot.files, snapshot.skipped_dirs);
+                self.snapshot_view = Some(summary);
+                scroll
+            }
+
+            Message::SnapshotLoaded(Err(e)) => {
+                self.status_message = format!("Error: {}", e);
+                Command::none()
+            }
+
+            Message::RemoveSnapshot(summary) => {
+                run_in_background(move || remove_snapshot(&summary), Message::SnapshotRemoved)
+            }
+
+            Message::SnapshotRemoved(Ok(index)) => {
+                self.compare_picks.retain(|pick| index.iter().any(|s| s.file == pick.file));
+                self.recent_scans = index;
+                self.status_message = "Snapshot removed.".into();
+                Command::none()
+            }
+
+            Message::ToggleComparePick(summary, picked) => {
+                self.compare_picks.retain(|pick| pick.file != summary.file);
+                if picked {
+                    // Keep the two most recent picks
+                    if self.compare_picks.len() == 2 {
+                        self.compare_picks.remove(0);
+                    }
+                    self.compare_picks.push(summary);
+                }
+                Command::none()
+            }
+
+            Message::ComparePressed => {
+                let [a, b] = match self.compare_picks.as_slice() {
+                    [a, b] => [a.clone(), b.clone()],
+                    _ => return Command::none(),
+                };
+                self.status_message = format!("Comparing two scans of {}...", a.root);
+                run_in_background(
+                    move || compare_snapshots(&load_snapshot(&a)?, &load_snapshot(&b)?),
+                    Message::ComparisonReady
+                )
+            }
+
+            Message::ComparisonReady(Ok(comparison)) => {
+                self.status_message = format!(
+                    "{} changed paths between {} and {}",
+                    comparison.deltas.len(),
+                    format_timestamp(comparison.older.created),
+                    format_timestamp(comparison.newer.created)
+                );
+                if !comparison.warnings.is_empty() {
+                    self.status_message.push_str(" - not every change is real, see the warnings");
+                }
+                self.comparison = Some(comparison);
+                self.scroll_results_to_top()
+            }
+
+            Message::ComparisonReady(Err(e)) => {
+                self.status_message = format!("Error: {}", e);
+                Command::none()
+            }
+
+            Message::ComparisonKindChanged(kind) => {
+                self.comparison_kind = kind;
+                self.scroll_results_to_top()
+            }
+
+            Message::CloseComparison => {
+                self.comparison = None;
+                self.scroll_results_to_top()
+            }
+
+            Message::ScanCompleted(_, Err(e)) => {
+                self.is_scanning = false;
+                self.scan_cancel = None;
+                self.status_message = format!("Scan error: {}", e);
+                Command::none()
+            }
+
+            // Sort changed in the Scan View (Temporary)
+            Message::SortChanged(method) => {
+                self.current_sort = method;
+                sort_files(&mut self.scanned_files, self.current_sort);
+                self.scroll_results_to_top()
+            }
+
+            // Sort changed in the Settings View (Buffer)
+            Message::SettingsDefaultSortChanged(method) => {
+                self.settings_default_sort = method;
+                Command::none()
+            }
+
+            Message::ResultViewChanged(view) => {
+                self.result_view = view;
+                self.pending_delete_file = None;
+                self.scroll_results_to_top()
+            }
+
+            Message::FilterMinSizeChanged(value) => {
+                self.filter_inputs.min_size = value;
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::FilterMaxSizeChanged(value) => {
+                self.filter_inputs.max_size = value;
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::FilterModifiedAfterChanged(value) => {
+                self.filter_inputs.modified_after = value;
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::FilterModifiedBeforeChanged(value) => {
+                self.filter_inputs.modified_before = value;
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::FilterKindChanged(kind) => {
+                self.filter_inputs.kind = kind;
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::FilterTypeChanged(file_type) => {
+                self.filter_inputs.file_type = file_type;
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::BakeFiltersToggled(bake) => {
+                self.bake_filters = bake;
+                Command::none()
+            }
+
+            Message::ClearFiltersPressed => {
+                self.filter_inputs = FilterInputs::default();
+                self.refresh_result_filter();
+                Command::none()
+            }
+
+            Message::FindDuplicatesPressed => {
+                // Hashing reads the files, which a snapshot view must not do
+                if self.duplicate_search.is_some() || self.scanned_files.is_empty() || self.snapshot_view.is_some() {
+                    return Command::none();
+                }
+                self.duplicate_generation += 1;
+                let generation = self.duplicate_generation;
+                let cancel = CancelToken::default();
+                self.duplicate_search = Some(cancel.clone());
+                self.duplicate_progress = "Grouping files by size...".into();
+                self.status_message = "Searching for duplicate files...".into();
+
+                let files = self.scanned_files.clone();
+                let (sender, receiver) = mpsc::unbounded();
+                std::thread::spawn(move || {
+                    let progress_sender = sender.clone();
+                    let result = find_duplicates(&files, &cancel, |stage| {
+                        let _ = progress_sender
+                            .unbounded_send(Message::DuplicatesProgress(generation, stage.to_string()));
+                    });
+                    let _ = sender.unbounded_send(Message::DuplicatesFound(generation, result));
+                });
+
+                Command::run(receiver, |message| message)
+            }
+
+            Message::CancelDuplicatesPressed => {
+                if let Some(search) = self.duplicate_search.take() {
+                    search.cancel();
+                    self.status_message = "Duplicate search cancelled.".into();
+                }
+                Command::none()
+            }
+
+            // Messages from a cancelled search, or one started against an older scan
+            Message::DuplicatesProgress(generation, _) | Message::DuplicatesFound(generation, _)
+                if generation != self.duplicate_generation || self.duplicate_search.is_none() =>
+            {
+                Command::none()
+            }
+
+            Message::DuplicatesProgress(_, stage) => {
+                self.duplicate_progress = stage;
+                Command::none()
+            }
+
+            Message::DuplicatesFound(_, Ok(groups)) => {
+                self.duplicate_search = None;
+                let wasted: u64 = groups.iter().map(|g| g.wasted()).sum();
+                self.status_message = format!(
+                    "Found {} groups of duplicates wasting {:.2} {}",
+                    groups.len(),
+                    self.config.unit.convert(wasted),
+                    self.config.unit
+                );
+                self.duplicates = Some(groups);
+                Command::none()
+            }
+
+            Message::DuplicatesFound(_, Err(e)) => {
+                self.duplicate_search = None;
+                self.status_message = format!("Duplicate search failed: {}", e);
+                Command::none()
+            }
+
+            Message::TreeOpen(id) => {
+                if self.scan_tree.as_ref().is_some_and(|tree| id < tree.nodes.len()) {
+                    self.tree_cursor = id;
+                    self.pending_delete_file = None;
+                    return self.scroll_results_to_top();
+                }
+                Command::none()
+            }
+
+            Message::MaxDepthChanged(value) => {
+                self.max_depth_buffer = value;
+                Command::none()
+            }
+
+            Message::MaxEntriesChanged(value) => {
+                self.max_entries_buffer = value;
+                Command::none()
+            }
+
+            Message::UnlimitedScanToggled(unlimited) => {
+                self.unlimited_scan_buffer = unlimited;
+                Command::none()
+            }
+
+            Message::ScanThreadsChanged(value) => {
+                self.scan_threads_buffer = value;
+                Command::none()
+            }
+
+            Message::DetectContentToggled(detect) => {
+                self.detect_content_buffer = detect;
+                Command::none()
+            }
+
+            Message::ExcludeInputChanged(value) => {
+                self.exclude_input = value;
+                Command::none()
+            }
+
+            Message::AddExcludePressed => {
+                let pattern = self.exclude_input.trim().to_string();
+                if pattern.is_empty() {
+                    return Command::none();
+                }
+                if let Err(e) = compile_pattern(&pattern) {
+                    self.status_message = format!("Error: Invalid exclude pattern {}", e);
+                    return Command::none();
+                }
+                if !self.exclude_buffer.contains(&pattern) {
+                    self.exclude_buffer.push(pattern);
+                }
+                self.exclude_input.clear();
+                Command::none()
+            }
+
+            Message::RemoveExclude(index) => {
+                if index < self.exclude_buffer.len() {
+                    self.exclude_buffer.remove(index);
+                }
+                Command::none()
+            }
+
+            Message::ResetExcludesPressed => {
+                self.exclude_buffer = default_exclude_patterns();
+                Command::none()
+            }
+
+            Message::PermanentDeleteToggled(permanent) => {
+                self.permanent_delete_buffer = permanent;
+                Command::none()
+            }
+
+            Message::ScanFilterChanged(new_filter) => {
+                self.scan_filter_buffer = new_filter;
+                Command::none()
+            }
+
+            Message::UnitChanged(unit) => {
+                self.selected_unit = unit;
+                Command::none()
+            }
+
+            Message::SaveSettingsPressed => {
+                if let Err(e) = FilterSet::parse(&self.scan_filter_buffer) {
+                    self.status_message = format!("Error: Invalid scan filter {}", e);
+                    return Command::none();
+                }
+                let max_depth = match self.max_depth_buffer.trim().parse::<usize>() {
+                    Ok(depth) => depth,
+                    Err(_) => {
+                        self.status_message = "Error: Max depth must be a whole number".into();
+                        return Command::none();
+                    }
+                };
+                let max_entries = match self.max_entries_buffer.trim().parse::<usize>() {
+                    Ok(entries) if entries > 0 => entries,
+                    _ => {
+                        self.status_message = "Error: Max entries must be a number above 0".into();
+                        return Command::none();
+                    }
+                };
+
+                let scan_threads = match self.scan_threads_buffer.trim().parse::<usize>() {
+                    Ok(threads) => threads,
+                    Err(_) => {
+                        self.status_message = "Error: Scan threads must be a whole number (0 = auto)".into();
+                        return Command::none();
+                    }
+                };
+
+                self.config.scan_threads = scan_threads;
+                self.config.exclude_patterns = self.exclude_buffer.clone();
+                self.config.permanent_delete = self.permanent_delete_buffer;
+                self.config.detect_content = self.detect_content_buffer;
+                self.config.max_depth = max_depth;
+                self.config.max_entries = max_entries;
+                self.config.unlimited_scan = self.unlimited_scan_buffer;
+                self.config.scan_filter = self.scan_filter_buffer.clone();
+                self.config.unit = self.selected_unit;
+                self.config.default_path = self.default_path_buffer.clone();
+                
+                // Save Default Sort
+                self.config.default_sort = self.settings_default_sort;
+                
+                // Also update current sort immediately to match new default
+                self.current_sort = self.settings_default_sort;
+
+                // Filter sizes are typed in the display unit
+                self.refresh_result_filter();
+
+                let config_to_save = self.config.clone();
+
+                self.status_message = "Saving settings...".into();
+
+                Command::perform(
+                    async move { save_config(&config_to_save).map_err(|e| e.to_string()) },
+                    Message::ConfigSaved
+                )
+            }
+
+            Message::ConfigSaved(Ok(())) => {
+                self.status_message = "Settings saved successfully!".into();
+                Command::none()
+            }
+
+            Message::ConfigSaved(Err(e)) => {
+                self.status_message = format!("Error saving settings: {}", e);
+                Command::none()
+            }
+
+            Message::RequestDelete(_) if self.snapshot_view.is_some() => Command::none(),
+
+            Message::RequestDelete(path_str) => {
+                self.pending_delete_file = Some(path_str);
+                self.status_message = "Waiting for confirmation...".into();
+                Command::none()
+            }
+
+            Message::CancelDelete => {
+                self.pending_delete_file = None;
+                self.status_message = "Deletion cancelled.".into();
+                Command::none()
+            }
+
+            Message::ConfirmDelete => {
+                if let Some(path_str) = &self.pending_delete_file {
+                    let p = path_str.clone();
+                    self.pending_delete_file = None;
+
+                    if self.config.deletes_permanently() {
+                        self.status_message = format!("Deleting {}...", p);
+                        let generation = self.scan_generation;
+                        run_in_background(
+                            move || delete_permanently(Path::new(&p)),
+                            move |report| Message::FileDeleted(generation, report)
+                        )
+                    } else {
+                        self.status_message = format!("Moving {} to the Trash...", p);
+                        let requested = p.clone();
+                        let generation = self.scan_generation;
+                        run_in_background(
+                            move || move_to_trash(Path::new(&p)),
+                            move |result| Message::FileTrashed(generation, requested, result)
+                        )
+                    }
+                } else {
+                    Command::none()
+                }
+            }
+
+            Message::FileDeleted(generation, report) => {
+                // A scan or snapshot opened in the meantime replaced the results the entry came from
+                if generation == self.scan_generation {
+                    let removed: HashSet<String> = report.removed.iter().cloned().collect();
+                    self.remove_paths_from_results(&removed);
+                }
+
+                let freed = format!("{:.2} {}", self.config.unit.convert(report.freed), self.config.unit);
+                self.status_message = match report.failures.len() {
+                    0 if report.removed.len() > 1 => format!(
+                        "Successfully deleted: {} ({} items, {})",
+                        report.path,
+                        report.removed.len(),
+                        freed
+                    ),
+                    0 => format!("Successfully deleted: {}", report.path),
+                    failed if report.removed.is_empty() => format!(
+                        "Failed to delete {}: {}{}",
+                        report.path,
+                        report.failures[0],
+                        if failed > 1 { format!(" (and {} more errors)", failed - 1) } else { String::new() }
+                    ),
+                    failed => format!(
+                        "Partially deleted {}: {} items removed ({}), {} could not be removed. First error: {}",
+                        report.path,
+                        report.removed.len(),
+                        freed,
+                        failed,
+                        report.failures[0]
+                    ),
+                };
+                Command::none()
+            }
+
+            Message::FileTrashed(generation, path, Ok(item)) => {
+                // As with FileDeleted: newer results, or a read-only snapshot, are left alone
+                if generation == self.scan_generation {
+                    let files = self
+                        .remove_from_results(std::slice::from_ref(&path))
+                        .remove(&path)
+                        .unwrap_or_default();
+                    self.journal.push(JournalEntry {
+                        description: format!("Trash {}", path),
+                        items: vec![JournalItem {
+                            path: path.clone(),
+                            action: Reversible::Trashed(item),
+                            files,
+                        }],
+                    });
+                }
+                self.status_message = format!("Moved to Trash: {}", path);
+                Command::none()
+            }
+
+            Message::FileTrashed(_, _, Err(e)) => {
+                self.status_message = format!("Failed to move to Trash: {}", e);
+                Command::none()
+            }
+
+            Message::ToggleSelected(path, checked) => {
+                if checked {
+                    self.selected.insert(path);
+                } else {
+                    self.selected.remove(&path);
+                }
+                self.refresh_selection_summary();
+                self.pending_batch = None;
+                Command::none()
+            }
+
+            Message::SelectAllVisiblePressed => {
+                let visible: Vec<String> = self.visible_entries().iter().map(|f| f.path.clone()).collect();
+                self.status_message = format!("Selected {} visible items", visible.len());
+                self.selected.extend(visible);
+                self.refresh_selection_summary();
+                self.pending_batch = None;
+                Command::none()
+            }
+
+            Message::SelectPatternChanged(pattern) => {
+                self.select_pattern = pattern;
+                Command::none()
+            }
+
+            Message::SelectMatchingPressed => {
+                let patterns = match FilterSet::parse(&self.select_pattern) {
+                    Ok(patterns) => patterns,
+                    Err(e) => {
+                        self.status_message = format!("Error: Invalid selection pattern {}", e);
+                        return Command::none();
+                    }
+                };
+                let matching: Vec<String> = self
+                    .visible_entries()
+                    .iter()
+                    .filter(|f| patterns.matches(Path::new(&f.path)))
+                    .map(|f| f.path.clone())
+                    .collect();
+                self.status_message = format!("Selected {} items matching '{}'", matching.len(), self.select_pattern.trim());
+                self.selected.extend(matching);
+                self.refresh_selection_summary();
+                self.pending_batch = None;
+                Command::none()
+            }
+
+            Message::SelectDuplicateCopiesPressed => {
+                // Everything but the first file of each group
+                if let Some(groups) = &self.duplicates {
+                    let copies: Vec<String> = groups
+                        .iter()
+                        .flat_map(|g| g.files.iter().skip(1))
+                        .map(|f| f.path.clone())
+                        .collect();
+                    self.status_message = format!("Selected {} extra copies", copies.len());
+                    self.selected.extend(copies);
+                    self.refresh_selection_summary();
+                    self.pending_batch = None;
+                }
+                Command::none()
+            }
+
+            Message::ClearSelectionPressed => {
+                self.selected.clear();
+                self.refresh_selection_summary();
+                self.pending_batch = None;
+                Command::none()
+            }
+
+            Message::CopySelectedPathsPressed => {
+                let paths: Vec<String> = self.selection_roots().iter().map(|f| f.path.clone()).collect();
+                self.status_message = format!("Copied {} paths to the clipboard", paths.len());
+                iced::clipboard::write(paths.join("\n"))
+            }
+
+            Message::RequestBatch(action) => {
+                if !self.selected.is_empty() && !self.batch_running && self.snapshot_view.is_none() {
+                    self.pending_delete_file = None;
+                    self.pending_batch = Some(action);
+                    self.status_message = "Waiting for confirmation...".into();
+                }
+                Command::none()
+            }
+
+            Message::BatchMovePressed => {
+                Command::perform(
+                    async {
+                        let handle = rfd::AsyncFileDialog::new()
+                            .set_title("Move Selected Items To")
+                            .pick_folder()
+                            .await;
+                        handle.map(|h| h.path().to_string_lossy().to_string())
+                    },
+                    Message::BatchMoveDestination
+                )
+            }
+
+            Message::BatchMoveDestination(Some(dest)) => {
+                self.update(Message::RequestBatch(BatchAction::Move(PathBuf::from(dest))))
+            }
+
+            Message::BatchMoveDestination(None) => Command::none(),
+
+            Message::ExportPressed(format) => {
+                if self.visible_entries().is_empty() {
+                    self.status_message = "Nothing to export: the current view lists no entries.".into();
+                    return Command::none();
+                }
+                Command::perform(
+                    async move {
+                        let extension = format.extension();
+                        let handle = rfd::AsyncFileDialog::new()
+                            .set_title(format!("Export Results as {}", format))
+                            .set_file_name(format!("disk-maid-results.{}", extension))
+                            .add_filter(format.to_string(), &[extension])
+                            .save_file()
+                            .await;
+                        handle.map(|h| h.path().to_string_lossy().to_string())
+                    },
+                    move |dest| Message::ExportDestination(format, dest)
+                )
+            }
+
+            Message::ExportDestination(format, Some(dest)) => {
+                // Not every platform's dialog adds the extension of the chosen filter
+                let dest = format.with_extension(&dest);
+                // Exactly what the current view lists, in its order: the open folder in the
+                // tree and chart views, the duplicate groups, or the whole filtered flat list
+                let records: Vec<ExportRecord> = self
+                    .visible_entries()
+                    .into_iter()
+                    .map(ExportRecord::new)
+                    .collect();
+                let count = records.len();
+                self.status_message = format!("Exporting {} items...", count);
+                let target = dest.clone();
+                run_in_background(
+                    move || {
+                        export_results(Path::new(&target), format, &records)
+                    },
+                    move |result| Message::ExportCompleted(dest, count, result)
+                )
+            }
+
+            Message::ExportDestination(_, None) => Command::none(),
+
+            Message::ExportCompleted(dest, count, result) => {
+                self.status_message = match result {
+                    Ok(()) => format!("Exported {} items to {}", count, dest),
+                    Err(e) => format!("Export failed: {}", e),
+                };
+                Command::none()
+            }
+
+            Message::CancelBatch => {
+                self.pending_batch = None;
+                self.status_message = "Batch cancelled.".into();
+                Command::none()
+            }
+
+            Message::ConfirmBatch => {
+                let Some(action) = self.pending_batch.take() else {
+                    return Command::none();
+                };
+                // A snapshot opened since the batch was requested is read-only
+                if self.snapshot_view.is_some() {
+                    return Command::none();
+                }
+                // A running undo could be restoring the very paths the batch would touch
+                if self.undo_running {
+                    self.pending_batch = Some(action);
+                    self.status_message = "Wait for the undo to finish, then confirm again.".into();
+                    return Command::none();
+                }
+                let entries: Vec<FileInfo> = self.selection_roots().into_iter().cloned().collect();
+                self.batch_running = true;
+                self.status_message = format!("{}: {} items...", action, entries.len());
+                let done = action.clone();
+                let generation = self.scan_generation;
+                run_in_background(
+                    move || run_batch(&action, &entries),
+                    move |report| Message::BatchCompleted(generation, done, report)
+                )
+            }
+
+            Message::BatchCompleted(generation, action, report) => {
+                self.batch_running = false;
+                // As with FileDeleted: newer results, or a read-only snapshot, are left alone.
+                // Otherwise one pass over the results, then the reversible part is journaled with what it dropped.
+                if generation == self.scan_generation {
+                    let mut paths = report.removed.clone();
+                    paths.extend(report.reversible.iter().map(|(path, _)| path.clone()));
+                    let mut dropped = self.remove_from_results(&paths);
+                    let items: Vec<JournalItem> = report
+                        .reversible
+                        .into_iter()
+                        .map(|(path, action)| {
+                            let files = dropped.remove(&path).unwrap_or_default();
+                            JournalItem { path, action, files }
+                        })
+                        .collect();
+                    if !items.is_empty() {
+                        self.journal.push(JournalEntry {
+                            description: format!("{} ({} items)", action, items.len()),
+                            items,
+                        });
+                    }
+                }
+
+                let verb = match &action {
+                    BatchAction::Trash => "Moved to Trash".to_string(),
+                    BatchAction::Delete => "Deleted".to_string(),
+                    BatchAction::Move(dest) => format!("Moved to {}", dest.display()),
+                };
+                let mut status = format!(
+                    "{} {} of {} items ({:.2} {})",
+                    verb,
+                    report.completed,
+                    report.attempted,
+                    self.config.unit.convert(report.bytes),
+                    self.config.unit
+                );
+                if let Some(first) = report.failures.first() {
+                    status.push_str(&format!(". {} errors, first: {}", report.failures.len(), first));
+                }
+                self.status_message = status;
+                Command::none()
+            }
+
+            Message::UndoPressed => {
+                // Never alongside a batch: both would work on the same paths
+                if self.undo_running || self.batch_running || self.snapshot_view.is_some() {
+                    return Command::none();
+                }
+                let Some(entry) = self.journal.pop() else {
+                    return Command::none();
+                };
+                self.undo_running = true;
+                self.status_message = format!("Undoing: {}...", entry.description);
+                run_in_background(
+                    move || {
+                        let outcomes = undo_journal_entry(&entry);
+                        (entry, outcomes)
+                    },
+                    |(entry, outcomes)| Message::UndoCompleted(entry, outcomes)
+                )
+            }
+
+            Message::UndoCompleted(entry, outcomes) => {
+                self.undo_running = false;
+                let mut restored = 0;
+                let mut conflicts = Vec::new();
+                let mut failures = Vec::new();
+                let mut left = Vec::new();
+                let mut restored_files = Vec::new();
+                for (item, outcome) in entry.items.into_iter().zip(outcomes) {
+                    match outcome {
+                        RestoreOutcome::Restored => {
+                            restored += 1;
+                            restored_files.extend(item.files);
+                        }
+                        RestoreOutcome::Conflict => {
+                            conflicts.push(item.action.original().display().to_string());
+                            left.push(item);
+                        }
+                        RestoreOutcome::Failed(e) => {
+                            failures.push(e);
+                            left.push(item);
+                        }
+                    }
+                }
+                // One sort and rebuild for the whole entry, however many items it held
+                self.restore_to_results(restored_files);
+
+                let mut status = format!("Undo {}: {} restored", entry.description, restored);
+                if !conflicts.is_empty() {
+                    status.push_str(&format!(
+                        ", {} not restored because something now exists at {}",
+                        conflicts.len(),
+                        conflicts.join(", ")
+                    ));
+                }
+                if let Some(first) = failures.first() {
+                    status.push_str(&format!(", {} failed, first: {}", failures.len(), first));
+                }
+                // Keep whatever could not be put back so it can be retried
+                if !left.is_empty() {
+                    status.push_str(". Press Undo again to retry the rest");
+                    self.journal.push(JournalEntry {
+                        description: entry.description,
+                        items: left,
+                    });
+                }
+                self.status_message = status;
+                Command::none()
+            }
+
+            Message::BackgroundTaskLost => {
+                // The worker died mid-way, so whatever it was doing may be half done
+                self.batch_running = false;
+                self.undo_running = false;
+                self.status_message = "Error: the background task stopped unexpectedly. Rescan to see the current state.".into();
+                Command::none()
+            }
+
+            Message::FocusEntry(id) => {
+                let Some(tree) = &self.scan_tree else {
+                    return Command::none();
+                };
+                let Some(node) = tree.nodes.get(id) else {
+                    return Command::none();
+                };
+                self.focused_entry = Some(node.info.path.clone());
+                self.tree_cursor = node.parent.unwrap_or(ScanTree::ROOT);
+                self.pending_delete_file = None;
+
+                // Bring the entry into view in the list next to the chart
+                let row = self
+                    .visible_entries()
+                    .iter()
+                    .position(|f| Some(&f.path) == self.focused_entry.as_ref())
+                    .unwrap_or(0);
+                // The ".." row sits above the entries of every folder below the root
+                let row = row + usize::from(self.tree_cursor != ScanTree::ROOT);
+                self.list_scroll.offset = row as f32 * ROW_HEIGHT;
+                scrollable::scroll_to(
+                    results_scroll_id(),
+                    scrollable::AbsoluteOffset { x: 0.0, y: self.list_scroll.offset },
+                )
+            }
+
+            Message::ResultsScrolled(viewport) => {
+                self.list_scroll = ListScroll {
+                    offset: viewport.absolute_offset().y,
+                    height: viewport.bounds().height,
+                };
+                Command::none()
+            }
+
+            Message::OpenFolder(path_str) => {
+                let path = PathBuf::from(&path_str);
+                if let Some(parent) = path.parent() {
+                    let _ = open::that(parent);
+                    self.status_message = format!("Opened folder for: {}", path_str);
+                } else {
+                    let _ = open::that(path);
+                }
+                Command::none()
+            }
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let content = match self.current_screen {
+            Screen::MainMenu => main_menu_view(),
+            Screen::FileScan => file_scan_view(self),
+            Screen::RecentScans => recent_scans_view(self),
+            Screen::Settings => settings_view(self),
+            Screen::Help => help_view(),
+        };
+
+        let layout = if self.current_screen != Screen::MainMenu {
+            column![
+                button(text("Back to Main Menu")).on_press(Message::BackToMainMenu),
+                vertical_space().height(10),
+                // Fills the space between the back button and the status bar
+                container(content).height(Length::Fill),
+                container(text(&self.status_message))
+                    .padding(10)
+                    .width(Length::Fill)
+                    .style(ContainerStyle::Base)
+            ]
+            .padding(20)
+            .align_items(Alignment::Start)
+        } else {
+            column![content]
+                .padding(20)
+                .align_items(Alignment::Center)
+        };
+
+        container(layout)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    }
+}
+
+// --- VIEW FUNCTIONS ---
+
+// Fill colour of a category in the treemap, sunburst and type breakdown
+fn category_color(category: FileCategory) -> Color {
+    match category {
+        FileCategory::Video => Color::from_rgb8(214, 87, 77),
+        FileCategory::Image => Color::from_rgb8(232, 163, 61),
+        FileCategory::Audio => Color::from_rgb8(186, 104, 200),
+        FileCategory::Archive => Color::from_rgb8(141, 110, 99),
+        FileCategory::Document => Color::from_rgb8(66, 165, 245),
+        FileCategory::Source => Color::from_rgb8(102, 187, 106),
+        FileCategory::Binary => Color::from_rgb8(120, 144, 156),
+        FileCategory::Other => Color::from_rgb8(90, 90, 90),
+    }
+}
+
+fn main_menu_view() -> Element<'static, Message> {
+    let file_scan_btn = button(text("File & Scan").size(20))
+        .on_press(Message::ScreenChanged(Screen::FileScan))
+        .padding(20)
+        .width(Length::Fixed(300.0));
+
+    let recent_btn = button(text("Recent Scans").size(20))
+        .on_press(Message::ScreenChanged(Screen::RecentScans))
+        .padding(20)
+        .width(Length::Fixed(300.0));
+
+    let settings_btn = button(text("Settings").size(20))
+        .on_press(Message::ScreenChanged(Screen::Settings))
+        .padding(20)
+        .width(Length::Fixed(300.0));
+
+    let help_btn = button(text("Help").size(20))
+        .on_press(Message::ScreenChanged(Screen::Help))
+        .padding(20)
+        .width(Length::Fixed(300.0));
+
+    let exit_btn = button(text("Exit").size(20))
+        .on_press(Message::ExitApp)
+        .padding(20)
+        .width(Length::Fixed(300.0))
+        .style(iced::theme::Button::Destructive);
+
+    column![
+        text("Disk Maid").size(36),
+        file_scan_btn,
+        recent_btn,
+        settings_btn,
+        help_btn,
+        exit_btn,
+    ]
+    .spacing(20)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+fn file_scan_view(app: &DiskViz) -> Element<'_, Message> {
+    let is_scanning = app.is_scanning;
+    let scan_path = &app.scan_path_buffer;
+    let files = &app.scanned_files;
+    let progress = &app.scan_progress;
+    let unit = app.config.unit;
+    let current_sort = app.current_sort;
+
+    let input_row = row![
+        text_input("Enter path (e.g., /home/user or C:\\Users)", scan_path)
+            .on_input(Message::ScanPathChanged)
+            .padding(10)
+            .width(Length::Fill),
+        button(text("📂 Browse"))
+            .on_press(Message::BrowseScanPathPressed)
+            .padding(10)
+            .style(iced::theme::Button::Secondary)
+    ]
+    .spacing(10);
+
+    let mut col = column![
+        text("File & Scan").size(28),
+        text("Select directory to scan:"),
+        input_row,
+    ];
+
+    let mut controls_row = row![].spacing(20).align_items(Alignment::Center);
+
+    if !is_scanning {
+        controls_row = controls_row.push(button(text("▶ Start Scan")).on_press(Message::StartScanPressed).padding(10));
+    } else {
+        controls_row = controls_row.push(button(text("⏹ Stop Scan")).on_press(Message::StopScanPressed).padding(10));
+        controls_row = controls_row.push(text(format!(
+            "Scanning... {} files, {} dirs, {:.2} {} ({:.1}s)",
+            progress.files_found,
+            progress.dirs_found,
+            unit.convert(progress.bytes_counted),
+            unit,
+            progress.elapsed.as_secs_f64()
+        )));
+    }
+
+    controls_row = controls_row.push(text("Sort By:"));
+    controls_row = controls_row.push(
+        pick_list(
+            vec![
+                SortMethod::NameAZ,
+                SortMethod::NameZA,
+                SortMethod::SizeLargest,
+                SortMethod::SizeSmallest,
+                SortMethod::Newest,
+                SortMethod::Oldest,
+            ],
+            Some(current_sort),
+            Message::SortChanged
+        )
+        .width(Length::Fixed(180.0))
+    );
+
+    if let Some(last) = app.journal.last().filter(|_| app.snapshot_view.is_none() && !app.batch_running) {
+        let mut undo = button(text(format!("↶ Undo: {}", last.description)).size(12))
+            .style(iced::theme::Button::Secondary)
+            .padding(5);
+        if !app.undo_running {
+            undo = undo.on_press(Message::UndoPressed);
+        }
+        controls_row = controls_row.push(undo);
+    }
+
+    col = col.push(controls_row);
+
+    if let Some(snapshot) = &app.snapshot_view {
+        col = col.push(
+            text(format!(
+                "📁 Snapshot of {} taken {} - read-only, start a scan to refresh it",
+                snapshot.root,
+                format_timestamp(snapshot.created)
+            ))
+            .style(iced::theme::Text::Color(Color::from_rgb8(240, 200, 80))),
+        );
+    }
+
+    if is_scanning && !progress.current_dir.is_empty() {
+        col = col.push(text(format!("Current: {}", progress.current_dir)).size(12));
+    }
+
+    if !is_scanning && !app.skipped_dirs.is_empty() {
+        let mut skipped = column![text(format!(
+            "{} subtrees were not fully scanned because of scan limits (see Settings):",
+            app.skipped_dirs.len()
+        ))
+        .size(12)];
+        for dir in app.skipped_dirs.iter().take(5) {
+            skipped = skipped.push(text(format!("  {}", dir)).size(12));
+        }
+        if app.skipped_dirs.len() > 5 {
+            skipped = skipped.push(text(format!("  ... and {} more", app.skipped_dirs.len() - 5)).size(12));
+        }
+        col = col.push(skipped);
+    }
+
+    col = col.push(result_filter_bar(app));
+
+    if !files.is_empty() {
+        col = col.push(
+            row![
+                text("View:"),
+                pick_list(ResultView::ALL, Some(app.result_view), Message::ResultViewChanged)
+                    .width(Length::Fixed(180.0)),
+                button(text("💾 Export CSV...").size(12))
+                    .on_press(Message::ExportPressed(ExportFormat::Csv))
+                    .style(iced::theme::Button::Secondary)
+                    .padding(5),
+                button(text("💾 Export JSON...").size(12))
+                    .on_press(Message::ExportPressed(ExportFormat::Json))
+                    .style(iced::theme::Button::Secondary)
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        );
+        col = col.push(selection_bar(app));
+    }
+
+    match (app.result_view, &app.scan_tree) {
+        (ResultView::Duplicates, _) if !files.is_empty() => {
+            col = col.push(duplicate_results(app));
+        }
+        (ResultView::Treemap, Some(tree)) => {
+            col = col.push(treemap_results(tree, app.tree_cursor, unit));
+        }
+        (ResultView::Sunburst, Some(tree)) => {
+            col = col.push(sunburst_results(app, tree));
+        }
+        (ResultView::Types, _) if !files.is_empty() => {
+            col = col.push(type_breakdown_results(&app.type_breakdown, unit));
+        }
+        (ResultView::Tree, Some(tree)) => {
+            col = col.push(tree_results(
+                tree,
+                app.tree_cursor,
+                current_sort,
+                &app.result_filter,
+                row_context(app),
+                app.list_scroll,
+            ));
+        }
+        _ if !files.is_empty() => {
+            let visible = app.visible_entries();
+            if app.result_filter.is_active() {
+                col = col.push(text(format!("Showing {} of {} items:", visible.len(), files.len())).size(18));
+            } else {
+                col = col.push(text(format!("Found {} items:", files.len())).size(18));
+            }
+
+            let ctx = row_context(app);
+            col = col.push(virtual_list(app.list_scroll, visible.len(), |i| {
+                let file = visible[i];
+                let label = text(size_label(file, &file.path, unit)).size(12).width(Length::Fill);
+                file_row(file, label.into(), i, &ctx)
+            }));
+        }
+        _ => {}
+    }
+
+    col.spacing(15).into()
+}
+
+// Selection helpers plus the actions that apply to every selected entry at once
+fn selection_bar(app: &DiskViz) -> Element<'_, Message> {
+    let unit = app.config.unit;
+
+    let select_row = row![
+        button(text("Select All Visible").size(12))
+            .on_press(Message::SelectAllVisiblePressed)
+            .style(iced::theme::Button::Secondary)
+            .padding(5),
+        text_input("Select by pattern, e.g. *.log, !keep*", &app.select_pattern)
+            .on_input(Message::SelectPatternChanged)
+            .on_submit(Message::SelectMatchingPressed)
+            .padding(5)
+            .width(Length::Fixed(260.0)),
+        button(text("Select Matching").size(12))
+            .on_press(Message::SelectMatchingPressed)
+            .style(iced::theme::Button::Secondary)
+            .padding(5),
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center);
+
+    if app.selected.is_empty() {
+        return select_row.into();
+    }
+
+    let total = app.selection_bytes;
+    let summary = format!(
+        "{} selected ({:.2} {})",
+        app.selected.len(),
+        unit.convert(total),
+        unit
+    );
+
+    let actions: Element<'_, Message> = if app.batch_running {
+        text(format!("{} - working...", summary)).into()
+    } else if let Some(action) = &app.pending_batch {
+        row![
+            text(format!(
+                "{} {} items, {:.2} {} in total?",
+                action,
+                app.selection_root_count,
+                unit.convert(total),
+                unit
+            )),
+            button(text("Yes, Go Ahead").size(12))
+                .on_press(Message::ConfirmBatch)
+                .style(iced::theme::Button::Destructive)
+                .padding(5),
+            button(text("Cancel").size(12))
+                .on_press(Message::CancelBatch)
+                .style(iced::theme::Button::Secondary)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    } else {
+        let mut actions = row![text(summary)].spacing(10).align_items(Alignment::Center);
+        if app.snapshot_view.is_none() {
+            if TRASH_SUPPORTED {
+                actions = actions.push(
+                    button(text("Trash").size(12))
+                        .on_press(Message::RequestBatch(BatchAction::Trash))
+                        .style(iced::theme::Button::Destructive)
+                        .padding(5),
+                );
+            }
+            actions = actions
+                .push(
+                    button(text("Delete").size(12))
+                        .on_press(Message::RequestBatch(BatchAction::Delete))
+                        .style(iced::theme::Button::Destructive)
+                        .padding(5),
+                )
+                .push(
+                    button(text("Move...").size(12))
+                        .on_press(Message::BatchMovePressed)
+                        .padding(5),
+                );
+        }
+        actions
+            .push(
+                button(text("Copy Paths").size(12))
+                    .on_press(Message::CopySelectedPathsPressed)
+                    .style(iced::theme::Button::Secondary)
+                    .padding(5),
+            )
+            .push(
+                button(text("Clear").size(12))
+                    .on_press(Message::ClearSelectionPressed)
+                    .style(iced::theme::Button::Secondary)
+                    .padding(5),
+            )
+            .into()
+    };
+
+    column![select_row, actions].spacing(10).into()
+}
+
+// Size / age / kind filters for the current results, optionally applied to the next scan
+fn result_filter_bar(app: &DiskViz) -> Element<'_, Message> {
+    let inputs = &app.filter_inputs;
+    let unit = app.config.unit;
+
+    let fields = row![
+        text(format!("Size ({}):", unit)),
+        text_input("min", &inputs.min_size)
+            .on_input(Message::FilterMinSizeChanged)
+            .width(Length::Fixed(80.0)),
+        text_input("max", &inputs.max_size)
+            .on_input(Message::FilterMaxSizeChanged)
+            .width(Length::Fixed(80.0)),
+        text("Modified:"),
+        text_input("after", &inputs.modified_after)
+            .on_input(Message::FilterModifiedAfterChanged)
+            .width(Length::Fixed(110.0)),
+        text_input("before", &inputs.modified_before)
+            .on_input(Message::FilterModifiedBeforeChanged)
+            .width(Length::Fixed(110.0)),
+        pick_list(EntryKind::ALL, Some(inputs.kind), Message::FilterKindChanged)
+            .width(Length::Fixed(150.0)),
+        pick_list(TypeFilter::choices(), Some(inputs.file_type), Message::FilterTypeChanged)
+            .width(Length::Fixed(170.0)),
+        checkbox("Apply to next scan", app.bake_filters).on_toggle(Message::BakeFiltersToggled),
+        button(text("Clear"))
+            .on_press(Message::ClearFiltersPressed)
+            .style(iced::theme::Button::Secondary),
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center);
+
+    let mut bar = column![fields].spacing(5);
+    if let Some(error) = &app.filter_error {
+        bar = bar.push(
+            text(format!("✖ {}", error))
+                .size(12)
+                .style(iced::theme::Text::Color(Color::from_rgb8(230, 90, 90))),
+        );
+    }
+    bar.into()
+}
+
+// Extensions listed in the breakdown; the rest are summed into one line
+const BREAKDOWN_EXTENSIONS: usize = 40;
+
+// Count, size and share of the scan for every category and the biggest extensions
+fn type_breakdown_results(breakdown: &TypeBreakdown, unit: Unit) -> Element<'_, Message> {
+    let total = &breakdown.total;
+    let stats_row = |color: Color, name: String, stats: &TypeStats| -> Element<'_, Message> {
+        let percent = stats.percent_of(total);
+        row![
+            text("■").style(iced::theme::Text::Color(color)),
+            text(name).size(14).width(Length::Fixed(140.0)),
+            text(format!("{} files", stats.files)).size(14).width(Length::Fixed(110.0)),
+            text(format!("{:.2} {}", unit.convert(stats.bytes), unit)).size(14).width(Length::Fixed(110.0)),
+            text(format!("{:.1}%", percent)).size(14).width(Length::Fixed(60.0)),
+            progress_bar(0.0..=100.0, percent)
+                .width(Length::Fixed(260.0))
+                .height(Length::Fixed(10.0)),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    };
+
+    let mut list = column![text("By category").size(20)].spacing(6);
+    for (category, stats) in &breakdown.categories {
+        list = list.push(stats_row(category_color(*category), category.to_string(), stats));
+    }
+
+    if !breakdown.content_types.is_empty() {
+        list = list.push(vertical_space().height(10));
+        list = list.push(text("By detected content").size(20));
+        for (content, stats) in &breakdown.content_types {
+            list = list.push(stats_row(category_color(content.category()), content.to_string(), stats));
+        }
+    }
+
+    list = list.push(vertical_space().height(10));
+    list = list.push(text("By extension").size(20));
+    for (extension, category, stats) in breakdown.extensions.iter().take(BREAKDOWN_EXTENSIONS) {
+        list = list.push(stats_row(category_color(*category), extension.clone(), stats));
+    }
+    if breakdown.extensions.len() > BREAKDOWN_EXTENSIONS {
+        let mut rest = TypeStats::default();
+        for (_, _, stats) in &breakdown.extensions[BREAKDOWN_EXTENSIONS..] {
+            rest.files += stats.files;
+            rest.bytes += stats.bytes;
+        }
+        let name = format!("{} others", breakdown.extensions.len() - BREAKDOWN_EXTENSIONS);
+        list = list.push(stats_row(category_color(FileCategory::Other), name, &rest));
+    }
+
+    column![
+        text(format!(
+            "{} files, {:.2} {} in total",
+            total.files,
+            unit.convert(total.bytes),
+            unit
+        ))
+        .size(18),
+        container(scrollable(list.padding(5)).height(Length::Fixed(450.0)))
+            .style(ContainerStyle::Base)
+            .padding(5),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn duplicate_results(app: &DiskViz) -> Element<'_, Message> {
+    let unit = app.config.unit;
+
+    if app.duplicate_search.is_some() {
+        return row![
+            button(text("Cancel"))
+                .on_press(Message::CancelDuplicatesPressed)
+                .style(iced::theme::Button::Secondary),
+            text(&app.duplicate_progress),
+        ]
+        .spacing(20)
+        .align_items(Alignment::Center)
+        .into();
+    }
+
+    if app.snapshot_view.is_some() && app.duplicates.is_none() {
+        return text("Finding duplicates reads file contents, so it needs a fresh scan instead of a snapshot.").into();
+    }
+
+    let find_button = button(text("🔍 Find Duplicates"))
+        .on_press(Message::FindDuplicatesPressed)
+        .padding(10);
+
+    let Some(groups) = &app.duplicates else {
+        return row![
+            find_button,
+            text("Compares file contents among the scanned files (size, first block, then full hash)."),
+        ]
+        .spacing(20)
+        .align_items(Alignment::Center)
+        .into();
+    };
+
+    let wasted: u64 = groups.iter().map(|g| g.wasted()).sum();
+    let summary = text(format!(
+        "{} duplicate groups, {:.2} {} wasted",
+        groups.len(),
+        unit.convert(wasted),
+        unit
+    ))
+    .size(18);
+
+    // Each group is a header row followed by one row per copy; `None` marks the header
+    let rows: Vec<(&DuplicateGroup, Option<usize>)> = groups
+        .iter()
+        .flat_map(|group| {
+            std::iter::once((group, None)).chain((0..group.files.len()).map(move |i| (group, Some(i))))
+        })
+        .collect();
+    let ctx = row_context(app);
+    let list = virtual_list(app.list_scroll, rows.len(), |row_index| match rows[row_index] {
+        (group, None) => container(
+            text(format!(
+                "{} copies of {:.2} {} - {:.2} {} wasted",
+                group.files.len(),
+                unit.convert(group.size),
+                unit,
+                unit.convert(group.wasted()),
+                unit
+            ))
+            .size(14),
+        )
+        .padding(5)
+        .width(Length::Fill)
+        .height(Length::Fixed(ROW_HEIGHT))
+        .style(ContainerStyle::Base)
+        .into(),
+        (group, Some(i)) => {
+            let file = &group.files[i];
+            let label = text(&file.path).size(12).width(Length::Fill);
+            file_row(file, label.into(), i, &ctx)
+        }
+    });
+
+    column![
+        row![
+            find_button,
+            button(text("Select Extra Copies"))
+                .on_press(Message::SelectDuplicateCopiesPressed)
+                .style(iced::theme::Button::Secondary)
+                .padding(10),
+            summary,
+        ]
+        .spacing(20)
+        .align_items(Alignment::Center),
+        list,
+    ]
+    .spacing(10)
+    .into()
+}
+
+// Every row of a virtualized list has this height, so scroll offsets map straight to row indices
+const ROW_HEIGHT: f32 = 36.0;
+const LIST_HEIGHT: f32 = 400.0;
+// Rows built above and below the viewport so fast scrolling does not flash empty space
+const LIST_OVERSCAN: usize = 10;
+
+fn results_scroll_id() -> scrollable::Id {
+    scrollable::Id::new("results")
+}
+
+// Scrollable list that only builds the rows inside the viewport; spacers stand in for the rest
+fn virtual_list<'a>(
+    scroll: ListScroll,
+    count: usize,
+    row_at: impl Fn(usize) -> Element<'a, Message>,
+) -> Element<'a, Message> {
+    // The stored offset can be stale after the list shrank; the scrollable clamps the same way
+    let max_offset = (count as f32 * ROW_HEIGHT - scroll.height).max(0.0);
+    let offset = scroll.offset.min(max_offset);
+    let first = ((offset / ROW_HEIGHT) as usize).saturating_sub(LIST_OVERSCAN).min(count);
+    let last = (((offset + scroll.height) / ROW_HEIGHT).ceil() as usize + LIST_OVERSCAN).min(count);
+
+    let mut rows = column![vertical_space().height(first as f32 * ROW_HEIGHT)].spacing(0);
+    for i in first..last {
+        rows = rows.push(row_at(i));
+    }
+    rows = rows.push(vertical_space().height((count - last) as f32 * ROW_HEIGHT));
+
+    container(
+        scrollable(rows)
+            .id(results_scroll_id())
+            .on_scroll(Message::ResultsScrolled)
+            .height(Length::Fixed(LIST_HEIGHT)),
+    )
+    .style(ContainerStyle::Base)
+    .padding(5)
+    .into()
+}
+
+// What every result row needs to know besides the entry itself
+struct RowContext<'a> {
+    unit: Unit,
+    pending_delete: &'a Option<String>,
+    permanent_delete: bool,
+    selected: &'a HashSet<String>,
+    // Entry picked in the sunburst, highlighted in the list
+    focused: Option<&'a str>,
+    // Browsing a snapshot: no delete buttons
+    read_only: bool,
+}
+
+fn row_context(app: &DiskViz) -> RowContext<'_> {
+    RowContext {
+        unit: app.config.unit,
+        pending_delete: &app.pending_delete_file,
+        permanent_delete: app.config.deletes_permanently(),
+        selected: &app.selected,
+        focused: app.focused_entry.as_deref(),
+        read_only: app.snapshot_view.is_some(),
+    }
+}
+
+// One result row: a selection checkbox, the given label, then the actions for that entry
+fn file_row<'a>(
+    file: &'a FileInfo,
+    label: Element<'a, Message>,
+    index: usize,
+    ctx: &RowContext,
+) -> Element<'a, Message> {
+    let unit = ctx.unit;
+    let (confirm_label, delete_label) = if ctx.permanent_delete {
+        ("Yes, Delete", "Delete")
+    } else {
+        ("Yes, Trash", "Trash")
+    };
+
+    let path = file.path.clone();
+    let select = checkbox("", ctx.selected.contains(&file.path))
+        .on_toggle(move |checked| Message::ToggleSelected(path.clone(), checked));
+
+    let mut row_item = row![select, label]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+    let is_pending_this = ctx.pending_delete.as_ref() == Some(&file.path);
+
+    if is_pending_this {
+        let question = if file.is_dir {
+            format!(
+                "Remove folder with {} files ({:.2} {})?",
+                file.file_count,
+                unit.convert(file.size),
+                unit
+            )
+        } else {
+            "Are you sure?".to_string()
+        };
+        row_item = row_item.push(text(question).size(12));
+
+        row_item = row_item.push(
+            button(text(confirm_label).size(12))
+                .on_press(Message::ConfirmDelete)
+                .style(iced::theme::Button::Destructive)
+                .padding(5)
+        );
+
+        row_item = row_item.push(
+            button(text("Cancel").size(12))
+                .on_press(Message::CancelDelete)
+                .style(iced::theme::Button::Secondary)
+                .padding(5)
+        );
+    } else {
+        row_item = row_item.push(
+            button(text("Go to Folder").size(12))
+                .on_press(Message::OpenFolder(file.path.clone()))
+                .style(iced::theme::Button::Secondary)
+                .padding(5)
+        );
+
+        if !ctx.read_only {
+            row_item = row_item.push(
+                button(text(delete_label).size(12))
+                    .on_press(Message::RequestDelete(file.path.clone()))
+                    .style(iced::theme::Button::Destructive)
+                    .padding(5)
+            );
+        }
+    }
+
+    let row_style = if ctx.focused == Some(file.path.as_str()) {
+        ContainerStyle::RowFocused
+    } else if index.is_multiple_of(2) {
+        ContainerStyle::RowEven
+    } else {
+        ContainerStyle::RowOdd
+    };
+
+    container(row_item)
+        .width(Length::Fill)
+        .height(Length::Fixed(ROW_HEIGHT))
+        .padding(5)
+        .center_y()
+        .clip(true)
+        .style(row_style)
+        .into()
+}
+
+// Drill-down view of one directory of the scan tree with a breadcrumb back to the root
+fn tree_results<'a>(
+    tree: &'a ScanTree,
+    cursor: usize,
+    sort: SortMethod,
+    filter: &ResultFilter,
+    ctx: RowContext,
+    scroll: ListScroll,
+) -> Element<'a, Message> {
+    let unit = ctx.unit;
+    let current = tree.node(cursor);
+    let summary = text(format!(
+        "{:.2} {} in {} files, {} folders",
+        unit.convert(current.info.size),
+        unit,
+        current.info.file_count,
+        current.dir_count
+    ))
+    .size(18);
+
+    let mut children = tree.sorted_children(cursor, sort);
+    children.retain(|&id| filter.matches(&tree.node(id).info));
+    // The ".." entry takes the first row when there is a parent to go back to
+    let offset = usize::from(current.parent.is_some());
+
+    let file_list = virtual_list(scroll, children.len() + offset, |row_index| {
+        if let (Some(parent), 0) = (current.parent, row_index) {
+            return container(
+                button(text("⬆ ..").size(12))
+                    .on_press(Message::TreeOpen(parent))
+                    .style(iced::theme::Button::Text)
+                    .padding(5)
+            )
+            .height(Length::Fixed(ROW_HEIGHT))
+            .into();
+        }
+        let i = row_index - offset;
+        let id = children[i];
+        let node = tree.node(id);
+        let label: Element<'a, Message> = if node.info.is_dir {
+            button(text(size_label(&node.info, &node.name, unit)).size(12))
+                .on_press(Message::TreeOpen(id))
+                .style(iced::theme::Button::Text)
+                .padding(0)
+                .width(Length::Fill)
+                .into()
+        } else {
+            text(size_label(&node.info, &node.name, unit))
+                .size(12)
+                .width(Length::Fill)
+                .into()
+        };
+        file_row(&node.info, label, i, &ctx)
+    });
+
+    column![
+        tree_breadcrumb(tree, cursor),
+        summary,
+        file_list,
+    ]
+    .spacing(10)
+    .into()
+}
+
+// Clickable path from the scan root down to `cursor`
+fn tree_breadcrumb(tree: &ScanTree, cursor: usize) -> Element<'_, Message> {
+    let mut breadcrumb = row![].spacing(2).align_items(Alignment::Center);
+    for (i, id) in tree.breadcrumb(cursor).into_iter().enumerate() {
+        if i > 0 {
+            breadcrumb = breadcrumb.push(text("/"));
+        }
+        breadcrumb = breadcrumb.push(
+            button(text(&tree.node(id).name))
+                .on_press(Message::TreeOpen(id))
+                .style(iced::theme::Button::Text)
+                .padding(2)
+        );
+    }
+    scrollable(breadcrumb)
+        .direction(scrollable::Direction::Horizontal(scrollable::Properties::default()))
+        .into()
+}
+
+// Snapshots of earlier scans, newest first
+fn recent_scans_view(app: &DiskViz) -> Element<'_, Message> {
+    let unit = app.config.unit;
+
+    let mut list = column![].spacing(5);
+    for (i, summary) in app.recent_scans.iter().enumerate() {
+        let details = format!(
+            "{} - {} files, {} dirs, {:.2} {}",
+            format_timestamp(summary.created),
+            summary.files,
+            summary.dirs,
+            unit.convert(summary.bytes),
+            unit
+        );
+        let picked = app.compare_picks.iter().any(|pick| pick.file == summary.file);
+        let pick = summary.clone();
+        let item = row![
+            checkbox("", picked).on_toggle(move |checked| Message::ToggleComparePick(pick.clone(), checked)),
+            column![text(&summary.root).size(16), text(details).size(12)]
+                .spacing(2)
+                .width(Length::Fill),
+            button(text("Open").size(12))
+                .on_press(Message::OpenSnapshot(summary.clone()))
+                .padding(5),
+            button(text("Remove").size(12))
+                .on_press(Message::RemoveSnapshot(summary.clone()))
+                .style(iced::theme::Button::Secondary)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        let style = if i.is_multiple_of(2) { ContainerStyle::RowEven } else { ContainerStyle::RowOdd };
+        list = list.push(container(item).width(Length::Fill).padding(5).style(style));
+    }
+
+    if let Some(comparison) = &app.comparison {
+        return comparison_view(app, comparison);
+    }
+
+    let compare_bar: Element<'_, Message> = match app.compare_picks.as_slice() {
+        [a, b] if a.root == b.root => button(text("Compare Ticked Scans"))
+            .on_press(Message::ComparePressed)
+            .padding(10)
+            .into(),
+        [_, _] => text("The ticked scans are of different folders; pick two scans of the same folder.").into(),
+        _ => text("Tick two scans of the same folder to see what changed between them.").size(14).into(),
+    };
+
+    let body: Element<'_, Message> = if app.recent_scans.is_empty() {
+        text("No saved scans yet. Every completed scan is kept here so it can be reopened without rescanning.").into()
+    } else {
+        scrollable(list).height(Length::Fill).into()
+    };
+
+    column![
+        text("Recent Scans").size(28),
+        text(format!("Opened snapshots are read-only; the last {} scans are kept.", MAX_SNAPSHOTS)).size(14),
+        compare_bar,
+        body,
+    ]
+    .spacing(15)
+    .into()
+}
+
+fn signed_size(delta: i64, unit: Unit) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{:.2} {}", sign, unit.convert(delta.unsigned_abs()), unit)
+}
+
+// What grew or shrank between two snapshots, net change first
+fn comparison_view<'a>(app: &'a DiskViz, comparison: &'a ScanComparison) -> Element<'a, Message> {
+    let unit = app.config.unit;
+    let net = comparison.net_change();
+    let net_color = if net > 0 { Color::from_rgb8(230, 90, 90) } else { Color::from_rgb8(90, 200, 120) };
+
+    let mut summary = column![
+        text(format!(
+            "{}: {} → {}",
+            comparison.older.root,
+            format_timestamp(comparison.older.created),
+            format_timestamp(comparison.newer.created)
+        ))
+        .size(16),
+        text(format!(
+            "Net change: {} ({:.2} {} → {:.2} {})",
+            signed_size(net, unit),
+            unit.convert(comparison.older.bytes),
+            unit,
+            unit.convert(comparison.newer.bytes),
+            unit
+        ))
+        .size(20)
+        .style(iced::theme::Text::Color(net_color)),
+        text(format!(
+            "{} added, {} removed, {} grown, {} shrunk",
+            comparison.count(ChangeKind::Added),
+            comparison.count(ChangeKind::Removed),
+            comparison.count(ChangeKind::Grown),
+            comparison.count(ChangeKind::Shrunk)
+        ))
+        .size(14),
+    ]
+    .spacing(5);
+    for warning in &comparison.warnings {
+        summary = summary.push(
+            text(format!("⚠ {}", warning))
+                .size(14)
+                .style(iced::theme::Text::Color(Color::from_rgb8(240, 200, 80))),
+        );
+    }
+
+    let deltas: Vec<&PathDelta> = comparison
+        .deltas
+        .iter()
+        .filter(|d| match app.comparison_kind {
+            EntryKind::All => true,
+            EntryKind::FilesOnly => !d.is_dir,
+            EntryKind::DirsOnly => d.is_dir,
+        })
+        .collect();
+
+    let controls = row![
+        button(text("← Back to Recent Scans"))
+            .on_press(Message::CloseComparison)
+            .style(iced::theme::Button::Secondary),
+        text("Show:"),
+        pick_list(EntryKind::ALL, Some(app.comparison_kind), Message::ComparisonKindChanged)
+            .width(Length::Fixed(150.0)),
+        text(format!("{} changes, largest growth first", deltas.len())).size(14),
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center);
+
+    let list = virtual_list(app.list_scroll, deltas.len(), |i| {
+        let delta = deltas[i];
+        let color = if delta.delta() > 0 { Color::from_rgb8(230, 90, 90) } else { Color::from_rgb8(90, 200, 120) };
+        let name = if delta.is_dir { format!("[DIR] {}", delta.path) } else { delta.path.clone() };
+        let item = row![
+            text(signed_size(delta.delta(), unit))
+                .size(14)
+                .style(iced::theme::Text::Color(color))
+                .width(Length::Fixed(120.0)),
+            text(delta.kind.to_string()).size(12).width(Length::Fixed(70.0)),
+            text(name).size(12).width(Length::Fill),
+            text(format!(
+                "{:.2} → {:.2} {}",
+                unit.convert(delta.old_size),
+                unit.convert(delta.new_size),
+                unit
+            ))
+            .size(12),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        let style = if i.is_multiple_of(2) { ContainerStyle::RowEven } else { ContainerStyle::RowOdd };
+        container(item)
+            .width(Length::Fill)
+            .height(Length::Fixed(ROW_HEIGHT))
+            .padding(5)
+            .center_y()
+            .clip(true)
+            .style(style)
+            .into()
+    });
+
+    column![text("Compare Scans").size(28), summary, controls, list]
+        .spacing(15)
+        .into()
+}
+
+fn settings_view(app: &DiskViz) -> Element<'_, Message> {
+    let filter = &app.scan_filter_buffer;
+    let unit = app.selected_unit;
+    let default_path = &app.default_path_buffer;
+    let default_sort = app.settings_default_sort;
+
+    let mut exclude_list = column![].spacing(5);
+    for (i, pattern) in app.exclude_buffer.iter().enumerate() {
+        exclude_list = exclude_list.push(
+            row![
+                text(pattern).width(Length::Fill),
+                button(text("✖").size(12))
+                    .on_press(Message::RemoveExclude(i))
+                    .style(iced::theme::Button::Secondary)
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        );
+    }
+    exclude_list = exclude_list.push(
+        row![
+            text_input("e.g., node_modules, .git or /mnt/backup", &app.exclude_input)
+                .on_input(Message::ExcludeInputChanged)
+                .on_submit(Message::AddExcludePressed)
+                .width(Length::Fill),
+            button(text("Add"))
+                .on_press(Message::AddExcludePressed)
+                .style(iced::theme::Button::Secondary),
+            button(text("Restore Defaults"))
+                .on_press(Message::ResetExcludesPressed)
+                .style(iced::theme::Button::Secondary),
+        ]
+        .spacing(10),
+    );
+
+    let filter_feedback = match FilterSet::parse(filter) {
+        Ok(set) => text(format!("✔ {}", set.describe()))
+            .size(12)
+            .style(iced::theme::Text::Color(Color::from_rgb8(120, 200, 120))),
+        Err(e) => text(format!("✖ {}", e))
+            .size(12)
+            .style(iced::theme::Text::Color(Color::from_rgb8(230, 90, 90))),
+    };
+
+    let path_input = row![
+        text_input("Leave empty for Home", default_path)
+            .on_input(Message::DefaultPathChanged)
+            .padding(10)
+            .width(Length::Fill),
+        button(text("📂 Browse"))
+            .on_press(Message::BrowseDefaultPathPressed)
+            .padding(10)
+            .style(iced::theme::Button::Secondary)
+    ].spacing(10);
+
+    // Nothing to choose where there is no Trash to move files to
+    let permanent_delete_toggle = if TRASH_SUPPORTED {
+        checkbox("Delete permanently instead of moving to the Trash", app.permanent_delete_buffer)
+            .on_toggle(Message::PermanentDeleteToggled)
+    } else {
+        checkbox("Delete permanently (the Trash is not supported on this system)", true)
+    };
+
+    let settings = column![
+        text("Settings").size(28),
+        
+        text("Scan Filter:"),
+        text_input("e.g., *.{jpg,png}; report_*.pdf; !*.tmp", filter).on_input(Message::ScanFilterChanged),
+        filter_feedback,
+        
+        text("Default Path:"),
+        path_input,
+
+        text("Display Unit:"),
+        pick_list(
+            vec![Unit::KB, Unit::MB, Unit::GB],
+            Some(unit),
+            Message::UnitChanged
+        ),
+        
+        // NEW: Default Sort Picker
+        text("Default Sort Order:"),
+        pick_list(
+            vec![
+                SortMethod::NameAZ,
+                SortMethod::NameZA,
+                SortMethod::SizeLargest,
+                SortMethod::SizeSmallest,
+                SortMethod::Newest,
+                SortMethod::Oldest,
+            ],
+            Some(default_sort),
+            Message::SettingsDefaultSortChanged
+        ),
+
+        text("Scan Limits:"),
+        row![
+            text("Max Depth:"),
+            text_input("e.g., 32", &app.max_depth_buffer)
+                .on_input(Message::MaxDepthChanged)
+                .width(Length::Fixed(100.0)),
+            text("Max Entries:"),
+            text_input("e.g., 200000", &app.max_entries_buffer)
+                .on_input(Message::MaxEntriesChanged)
+                .width(Length::Fixed(140.0)),
+            checkbox("Unlimited", app.unlimited_scan_buffer)
+                .on_toggle(Message::UnlimitedScanToggled),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center),
+
+        text("Exclude (never scanned):"),
+        exclude_list,
+
+        text("Deleting:"),
+        permanent_delete_toggle,
+
+        text("Scan Threads (0 = one per CPU, 1 = single-threaded):"),
+        text_input("0", &app.scan_threads_buffer)
+            .on_input(Message::ScanThreadsChanged)
+            .width(Length::Fixed(100.0)),
+
+        text("File Types:"),
+        checkbox(
+            "Identify file types from content (slower: reads the start of every file)",
+            app.detect_content_buffer
+        )
+        .on_toggle(Message::DetectContentToggled),
+
+        vertical_space().height(20),
+        
+        button(text("Save Settings"))
+            .on_press(Message::SaveSettingsPressed)
+            .padding(10)
+    ]
+    .spacing(15)
+    .padding([0, 15, 0, 0]);
+
+    // The settings list outgrows small windows, so let it scroll
+    scrollable(settings).height(Length::Fill).into()
+}
+
+fn help_view() -> Element<'static, Message> {
+    column![
+        text("Help & About").size(28),
+        text("How to Use:").size(20),
+        text("1. Click 'File & Scan' from the main menu").size(16),
+        text("2. Click '📂 Browse' or type a path manually").size(16),
+        text("3. Click 'Start Scan'").size(16),
+        text("4. Use 'Sort By' to organize files").size(16),
+        text("   Switch 'View' to 'Folder Tree' to click into folders").size(16),
+        text("   'Treemap' draws each file as a box sized by bytes; click a box to zoom in").size(16),
+        text("   'Sunburst' shows folder levels as rings next to the list; click a ring to centre on it").size(16),
+        text("   'File Types' shows which kinds of files take up the space").size(16),
+        text("   Turn on content detection in Settings to tell files apart by their bytes, not their names").size(16),
+        text("   Pick the 'Duplicates' view to find files with identical content").size(16),
+        text("   'Export CSV...' and 'Export JSON...' save the filtered list in that format").size(16),
+        text("5. Click 'Go to Folder' to open location").size(16),
+        text("6. Click 'Trash' -> 'Yes' to move a file or folder to the Trash").size(16),
+        text("7. Tick several rows (or 'Select All Visible') to trash, delete, move or copy them at once").size(16),
+        text("8. 'Undo' puts the last trashed or moved items back where they were").size(16),
+        text("9. Every finished scan is saved; reopen one from 'Recent Scans' without rescanning").size(16),
+        text("   Tick two scans of the same folder there and 'Compare' to see what grew in between").size(16),
+        text("10. Over SSH, run 'disk_maid tui <folder>' to browse a scan in the terminal ('disk_maid --help' for more)").size(16),
+        vertical_space().height(20),
+        text("Settings:").size(20),
+        text("• Set a 'Default Path' to auto-load").size(16),
+        text("• Set 'Default Sort Order' for consistent listing").size(16),
+        text("• Change filters and units").size(16),
+        text("• Opt in to permanent deletion instead of the Trash").size(16),
+        text("• Dates in the File & Scan filters take YYYY-MM-DD or an age like 365d").size(16),
+        text("• Exclude folders such as node_modules or /mnt/backup from every scan").size(16),
+        text("• Filters: globs separated by ',' or ';', {a,b} alternatives, '!' to exclude").size(16),
+        text("• Adjust scan depth / entry limits, or make scans unlimited").size(16),
+        vertical_space().height(20),
+        text("About:").size(20),
+        text("Disk Maid v2.5.0").size(16),
+        text("Now with Default Sorting!").size(16),
+    ]
+    .spacing(10)
+    .into()
+}
+
+// --- TREEMAP ---
+
+const TREEMAP_HEIGHT: f32 = 450.0;
+// Folders deeper than this below the zoomed one are drawn as a single block
+const TREEMAP_MAX_DEPTH: usize = 6;
+// Tiles thinner than this are neither drawn nor descended into
+const TREEMAP_MIN_SIDE: f32 = 3.0;
+// Strip at the top of a folder tile that holds its name
+const TREEMAP_HEADER: f32 = 14.0;
+
+#[derive(Debug, Clone, Copy)]
+struct TreemapTile {
+    bounds: Rectangle,
+    node: usize,
+    depth: usize,
+}
+
+// Squarified layout (Bruls, Huizing, van Wijk): each row of tiles keeps growing while that
+// brings its worst aspect ratio closer to square. `sizes` must be sorted largest first.
+fn squarify(sizes: &[f64], area: Rectangle) -> Vec<Rectangle> {
+    let total: f64 = sizes.iter().sum();
+    let (mut x, mut y) = (area.x as f64, area.y as f64);
+    let (mut width, mut height) = (area.width as f64, area.height as f64);
+    if total <= 0.0 || width <= 0.0 || height <= 0.0 {
+        return vec![Rectangle::new(area.position(), Size::ZERO); sizes.len()];
+    }
+    let scale = width * height / total;
+
+    let mut rects = Vec::with_capacity(sizes.len());
+    let mut start = 0;
+    while start < sizes.len() {
+        let side = width.min(height);
+        let largest = sizes[start] * scale;
+        let mut row_area = largest;
+        let mut worst = worst_aspect_ratio(largest, largest, row_area, side);
+        let mut end = start + 1;
+        while end < sizes.len() {
+            let candidate = row_area + sizes[end] * scale;
+            let ratio = worst_aspect_ratio(largest, sizes[end] * scale, candidate, side);
+            if ratio > worst {
+                break;
+            }
+            worst = ratio;
+            row_area = candidate;
+            end += 1;
+        }
+
+        // The row is laid along the shorter side of what is left
+        let thickness = if side > 0.0 { row_area / side } else { 0.0 };
+        let mut offset = 0.0;
+        for &size in &sizes[start..end] {
+            let length = if thickness > 0.0 { size * scale / thickness } else { 0.0 };
+            let (rx, ry, rw, rh) = if width >= height {
+                (x, y + offset, thickness, length)
+            } else {
+                (x + offset, y, length, thickness)
+            };
+            rects.push(Rectangle {
+                x: rx as f32,
+                y: ry as f32,
+                width: rw as f32,
+                height: rh as f32,
+            });
+            offset += length;
+        }
+        if width >= height {
+            x += thickness;
+            width = (width - thickness).max(0.0);
+        } else {
+            y += thickness;
+            height = (height - thickness).max(0.0);
+        }
+        start = end;
+    }
+    rects
+}
+
+fn worst_aspect_ratio(largest: f64, smallest: f64, row_area: f64, side: f64) -> f64 {
+    let side = side * side;
+    let area = row_area * row_area;
+    (side * largest / area).max(area / (side * smallest))
+}
+
+// Tiles for everything below `root`, parents before their children
+fn treemap_tiles(tree: &ScanTree, root: usize, area: Rectangle) -> Vec<TreemapTile> {
+    let mut tiles = Vec::new();
+    layout_treemap_directory(tree, root, area, 0, &mut tiles);
+    tiles
+}
+
+fn layout_treemap_directory(
+    tree: &ScanTree,
+    dir: usize,
+    area: Rectangle,
+    depth: usize,
+    tiles: &mut Vec<TreemapTile>,
+) {
+    let children: Vec<usize> = tree
+        .sorted_children(dir, SortMethod::SizeLargest)
+        .into_iter()
+        .filter(|&id| tree.node(id).info.size > 0)
+        .collect();
+    let sizes: Vec<f64> = children.iter().map(|&id| tree.node(id).info.size as f64).collect();
+
+    for (bounds, id) in squarify(&sizes, area).into_iter().zip(children) {
+        if bounds.width < TREEMAP_MIN_SIDE || bounds.height < TREEMAP_MIN_SIDE {
+            continue;
+        }
+        tiles.push(TreemapTile { bounds, node: id, depth });
+
+        let inner = Rectangle {
+            x: bounds.x + 1.0,
+            y: bounds.y + TREEMAP_HEADER,
+            width: bounds.width - 2.0,
+            height: bounds.height - TREEMAP_HEADER - 1.0,
+        };
+        if tree.node(id).info.is_dir
+            && depth + 1 < TREEMAP_MAX_DEPTH
+            && inner.width >= TREEMAP_MIN_SIDE
+            && inner.height >= TREEMAP_MIN_SIDE
+        {
+            layout_treemap_directory(tree, id, inner, depth + 1, tiles);
+        }
+    }
+}
+
+// Canvas text is not clipped, so labels are cut down to roughly what fits
+fn fit_label(label: &str, width: f32) -> Option<String> {
+    let max_chars = ((width - 6.0) / 7.0).max(0.0) as usize;
+    if max_chars < 3 {
+        return None;
+    }
+    if label.chars().count() <= max_chars {
+        Some(label.to_string())
+    } else {
+        Some(format!("{}…", label.chars().take(max_chars - 1).collect::<String>()))
+    }
+}
+
+// Path and size of the hovered entry next to the cursor, kept inside the canvas
+fn draw_node_tooltip(frame: &mut canvas::Frame, bounds: Rectangle, position: Point, info: &FileInfo, unit: Unit) {
+    let details = if info.is_dir {
+        format!("{:.2} {} in {} files", unit.convert(info.size), unit, info.file_count)
+    } else {
+        format!(
+            "{:.2} {} - {}",
+            unit.convert(info.size),
+            unit,
+            info.category()
+        )
+    };
+    let chars = info.path.chars().count().max(details.chars().count());
+    let size = Size::new(chars as f32 * 7.0 + 12.0, 38.0);
+    let x = if position.x + 14.0 + size.width > bounds.width {
+        (position.x - size.width - 4.0).max(0.0)
+    } else {
+        position.x + 14.0
+    };
+    let y = if position.y + 14.0 + size.height > bounds.height {
+        (position.y - size.height - 4.0).max(0.0)
+    } else {
+        position.y + 14.0
+    };
+    frame.fill_rectangle(Point::new(x, y), size, Color::from_rgba8(0, 0, 0, 0.85));
+    for (line, content) in [info.path.clone(), details].into_iter().enumerate() {
+        frame.fill_text(canvas::Text {
+            content,
+            position: Point::new(x + 6.0, y + 4.0 + line as f32 * 16.0),
+            color: Color::WHITE,
+            size: 12.0.into(),
+            ..canvas::Text::default()
+        });
+    }
+}
+
+struct Treemap<'a> {
+    tree: &'a ScanTree,
+    root: usize,
+    unit: Unit,
+}
+
+// What a layout was computed for: zoomed folder, tree shape and canvas size
+type TreemapKey = (usize, usize, u64, Size);
+
+#[derive(Default)]
+struct TreemapState {
+    cache: canvas::Cache,
+    layout: RefCell<Option<(TreemapKey, Vec<TreemapTile>)>>,
+}
+
+impl Treemap<'_> {
+    // Tiles for the current folder and canvas size, only recomputed when either changed
+    fn tiles<'s>(&self, state: &'s TreemapState, size: Size) -> std::cell::Ref<'s, [TreemapTile]> {
+        let key = (self.root, self.tree.nodes.len(), self.tree.node(self.root).info.size, size);
+        if state.layout.borrow().as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let tiles = treemap_tiles(self.tree, self.root, Rectangle::new(Point::ORIGIN, size));
+            *state.layout.borrow_mut() = Some((key, tiles));
+            state.cache.clear();
+        }
+        std::cell::Ref::map(state.layout.borrow(), |layout| {
+            layout.as_ref().map_or(&[][..], |(_, tiles)| tiles.as_slice())
+        })
+    }
+
+    // Deepest tile under the cursor
+    fn hovered(tiles: &[TreemapTile], bounds: Rectangle, cursor: mouse::Cursor) -> Option<TreemapTile> {
+        let position = cursor.position_in(bounds)?;
+        tiles.iter().rev().find(|tile| tile.bounds.contains(position)).copied()
+    }
+}
+
+impl canvas::Program<Message> for Treemap<'_> {
+    type State = TreemapState;
+
+    fn update(
+        &self,
+        state: &mut TreemapState,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let target = match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let tiles = self.tiles(state, bounds.size());
+                Self::hovered(&tiles, bounds, cursor).map(|tile| {
+                    // Clicking a file zooms into the folder that holds it
+                    let node = self.tree.node(tile.node);
+                    if node.info.is_dir {
+                        tile.node
+                    } else {
+                        node.parent.unwrap_or(ScanTree::ROOT)
+                    }
+                })
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right))
+                if cursor.is_over(bounds) =>
+            {
+                self.tree.node(self.root).parent
+            }
+            _ => None,
+        };
+        match target {
+            Some(id) if id != self.root => (canvas::event::Status::Captured, Some(Message::TreeOpen(id))),
+            _ => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &TreemapState,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let tiles = self.tiles(state, bounds.size());
+        let border = canvas::Stroke::default()
+            .with_color(Color::from_rgb8(15, 15, 15))
+            .with_width(1.0);
+
+        let map = state.cache.draw(renderer, bounds.size(), |frame| {
+            for tile in tiles.iter() {
+                let node = self.tree.node(tile.node);
+                let (fill, label_color) = if node.info.is_dir {
+                    let shade = 35 + (tile.depth.min(6) as u8) * 10;
+                    (Color::from_rgb8(shade, shade, shade + 10), Color::WHITE)
+                } else {
+                    (category_color(node.info.category()), Color::BLACK)
+                };
+                frame.fill_rectangle(tile.bounds.position(), tile.bounds.size(), fill);
+                frame.stroke(
+                    &canvas::Path::rectangle(tile.bounds.position(), tile.bounds.size()),
+                    border.clone(),
+                );
+                if tile.bounds.height >= TREEMAP_HEADER {
+                    if let Some(label) = fit_label(&node.name, tile.bounds.width) {
+                        frame.fill_text(canvas::Text {
+                            content: label,
+                            position: Point::new(tile.bounds.x + 3.0, tile.bounds.y + 1.0),
+                            color: label_color,
+                            size: 11.0.into(),
+                            ..canvas::Text::default()
+                        });
+                    }
+                }
+            }
+        });
+
+        let mut overlay = canvas::Frame::new(renderer, bounds.size());
+        if let (Some(tile), Some(position)) = (Self::hovered(&tiles, bounds, cursor), cursor.position_in(bounds)) {
+            let node = self.tree.node(tile.node);
+            overlay.stroke(
+                &canvas::Path::rectangle(tile.bounds.position(), tile.bounds.size()),
+                canvas::Stroke::default().with_color(Color::WHITE).with_width(2.0),
+            );
+
+            draw_node_tooltip(&mut overlay, bounds, position, &node.info, self.unit);
+        }
+
+        vec![map, overlay.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &TreemapState,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+// Treemap of the folder the tree cursor points at, with the same breadcrumb as the tree view
+fn treemap_results(tree: &ScanTree, cursor: usize, unit: Unit) -> Element<'_, Message> {
+    let current = tree.node(cursor);
+    let summary = text(format!(
+        "{:.2} {} in {} files - click to zoom in, right-click to zoom out",
+        unit.convert(current.info.size),
+        unit,
+        current.info.file_count
+    ))
+    .size(18);
+
+    let mut legend = row![].spacing(15).align_items(Alignment::Center);
+    for category in FileCategory::ALL {
+        legend = legend.push(
+            row![
+                text("■").style(iced::theme::Text::Color(category_color(category))),
+                text(category.to_string()).size(12),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+        );
+    }
+
+    column![
+        tree_breadcrumb(tree, cursor),
+        summary,
+        legend,
+        container(
+            canvas(Treemap { tree, root: cursor, unit })
+                .width(Length::Fill)
+                .height(Length::Fixed(TREEMAP_HEIGHT)),
+        )
+        .style(ContainerStyle::Base)
+        .padding(5),
+    ]
+    .spacing(10)
+    .into()
+}
+
+// --- SUNBURST ---
+
+const SUNBURST_HEIGHT: f32 = 450.0;
+// Rings drawn around the centre, one per folder level
+const SUNBURST_RINGS: usize = 5;
+// Arcs narrower than this (in radians) are skipped
+const SUNBURST_MIN_SPAN: f32 = 0.004;
+
+#[derive(Debug, Clone, Copy)]
+struct SunburstSegment {
+    node: usize,
+    // 1 is the innermost ring
+    ring: usize,
+    // Clockwise from 12 o'clock, in radians
+    start: f32,
+    end: f32,
+}
+
+// Arcs for everything below `root`, each child sharing its parent's span by size
+fn sunburst_segments(tree: &ScanTree, root: usize) -> Vec<SunburstSegment> {
+    let mut segments = Vec::new();
+    layout_sunburst_ring(tree, root, 0.0, std::f32::consts::TAU, 1, &mut segments);
+    segments
+}
+
+fn layout_sunburst_ring(
+    tree: &ScanTree,
+    dir: usize,
+    start: f32,
+    end: f32,
+    ring: usize,
+    segments: &mut Vec<SunburstSegment>,
+) {
+    let total = tree.node(dir).info.size;
+    if ring > SUNBURST_RINGS || total == 0 {
+        return;
+    }
+    let mut angle = start;
+    for id in tree.sorted_children(dir, SortMethod::SizeLargest) {
+        let node = tree.node(id);
+        let span = (end - start) * (node.info.size as f64 / total as f64) as f32;
+        if span >= SUNBURST_MIN_SPAN {
+            segments.push(SunburstSegment { node: id, ring, start: angle, end: angle + span });
+            if node.info.is_dir {
+                layout_sunburst_ring(tree, id, angle, angle + span, ring + 1, segments);
+            }
+        }
+        angle += span;
+    }
+}
+
+// Folders get a hue from where they sit on the circle, darker further out
+fn sunburst_dir_color(segment: &SunburstSegment) -> Color {
+    let hue = (segment.start + segment.end) / 2.0 / std::f32::consts::TAU * 6.0;
+    let value = 0.85 - segment.ring as f32 * 0.08;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    // Keep some saturation headroom so labels and outlines stay readable
+    let mix = |c: f32| (0.35 + 0.65 * c) * value;
+    Color::from_rgb(mix(r), mix(g), mix(b))
+}
+
+struct Sunburst<'a> {
+    tree: &'a ScanTree,
+    root: usize,
+    unit: Unit,
+    selected: &'a HashSet<String>,
+    focused: Option<&'a str>,
+}
+
+// Geometry of the chart within the canvas
+#[derive(Debug, Clone, Copy)]
+struct SunburstFrame {
+    center: Point,
+    hole: f32,
+    ring_width: f32,
+}
+
+impl SunburstFrame {
+    fn new(size: Size) -> Self {
+        let radius = (size.width.min(size.height) / 2.0 - 8.0).max(10.0);
+        let hole = radius * 0.2;
+        SunburstFrame {
+            center: Point::new(size.width / 2.0, size.height / 2.0),
+            hole,
+            ring_width: (radius - hole) / SUNBURST_RINGS as f32,
+        }
+    }
+
+    fn point(&self, radius: f32, angle: f32) -> Point {
+        Point::new(self.center.x + radius * angle.sin(), self.center.y - radius * angle.cos())
+    }
+
+    // Annular sector for one segment, traced as a polygon
+    fn arc_path(&self, segment: &SunburstSegment) -> canvas::Path {
+        let inner = self.hole + (segment.ring - 1) as f32 * self.ring_width;
+        let outer = inner + self.ring_width;
+        let steps = ((segment.end - segment.start) / 0.03).ceil().max(1.0) as usize;
+        canvas::Path::new(|builder| {
+            builder.move_to(self.point(inner, segment.start));
+            for step in 0..=steps {
+                let angle = segment.start + (segment.end - segment.start) * step as f32 / steps as f32;
+                builder.line_to(self.point(outer, angle));
+            }
+            for step in (0..=steps).rev() {
+                let angle = segment.start + (segment.end - segment.start) * step as f32 / steps as f32;
+                builder.line_to(self.point(inner, angle));
+            }
+            builder.close();
+        })
+    }
+
+    // Ring (0 = centre) and angle under a point, if it is on the chart
+    fn locate(&self, position: Point) -> Option<(usize, f32)> {
+        let dx = position.x - self.center.x;
+        let dy = position.y - self.center.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance < self.hole {
+            return Some((0, 0.0));
+        }
+        let ring = ((distance - self.hole) / self.ring_width) as usize + 1;
+        if ring > SUNBURST_RINGS {
+            return None;
+        }
+        let angle = dx.atan2(-dy).rem_euclid(std::f32::consts::TAU);
+        Some((ring, angle))
+    }
+}
+
+// What a layout was computed for: centre folder and tree shape
+type SunburstKey = (usize, usize, u64);
+
+#[derive(Default)]
+struct SunburstState {
+    cache: canvas::Cache,
+    layout: RefCell<Option<(SunburstKey, Vec<SunburstSegment>)>>,
+}
+
+impl Sunburst<'_> {
+    fn segments<'s>(&self, state: &'s SunburstState) -> std::cell::Ref<'s, [SunburstSegment]> {
+        let key = (self.root, self.tree.nodes.len(), self.tree.node(self.root).info.size);
+        if state.layout.borrow().as_ref().is_none_or(|(cached, _)| *cached != key) {
+            *state.layout.borrow_mut() = Some((key, sunburst_segments(self.tree, self.root)));
+            state.cache.clear();
+        }
+        std::cell::Ref::map(state.layout.borrow(), |layout| {
+            layout.as_ref().map_or(&[][..], |(_, segments)| segments.as_slice())
+        })
+    }
+
+    // `Some(None)` means the centre circle
+    fn hovered(
+        segments: &[SunburstSegment],
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<Option<SunburstSegment>> {
+        let position = cursor.position_in(bounds)?;
+        match SunburstFrame::new(bounds.size()).locate(position)? {
+            (0, _) => Some(None),
+            (ring, angle) => segments
+                .iter()
+                .find(|s| s.ring == ring && s.start <= angle && angle < s.end)
+                .map(|s| Some(*s)),
+        }
+    }
+}
+
+impl canvas::Program<Message> for Sunburst<'_> {
+    type State = SunburstState;
+
+    fn update(
+        &self,
+        state: &mut SunburstState,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let message = match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let segments = self.segments(state);
+                match Self::hovered(&segments, bounds, cursor) {
+                    // The centre stands for the current folder; clicking it goes up a level
+                    Some(None) => self.tree.node(self.root).parent.map(Message::TreeOpen),
+                    Some(Some(segment)) if self.tree.node(segment.node).info.is_dir => {
+                        Some(Message::TreeOpen(segment.node))
+                    }
+                    Some(Some(segment)) => Some(Message::FocusEntry(segment.node)),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        match message {
+            Some(message) => (canvas::event::Status::Captured, Some(message)),
+            None => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &SunburstState,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let segments = self.segments(state);
+        let chart = SunburstFrame::new(bounds.size());
+        let root = self.tree.node(self.root);
+
+        let rings = state.cache.draw(renderer, bounds.size(), |frame| {
+            let border = canvas::Stroke::default()
+                .with_color(Color::from_rgb8(15, 15, 15))
+                .with_width(1.0);
+            for segment in segments.iter() {
+                let node = self.tree.node(segment.node);
+                let fill = if node.info.is_dir {
+                    sunburst_dir_color(segment)
+                } else {
+                    category_color(node.info.category())
+                };
+                let path = chart.arc_path(segment);
+                frame.fill(&path, fill);
+                frame.stroke(&path, border.clone());
+            }
+
+            frame.fill(&canvas::Path::circle(chart.center, chart.hole), Color::from_rgb8(10, 40, 70));
+            let label = fit_label(&root.name, chart.hole * 2.0).unwrap_or_default();
+            for (line, content) in [label, format!("{:.1} {}", self.unit.convert(root.info.size), self.unit)]
+                .into_iter()
+                .enumerate()
+            {
+                frame.fill_text(canvas::Text {
+                    content,
+                    position: Point::new(chart.center.x, chart.center.y - 8.0 + line as f32 * 16.0),
+                    color: Color::WHITE,
+                    size: 12.0.into(),
+                    horizontal_alignment: iced::alignment::Horizontal::Center,
+                    vertical_alignment: iced::alignment::Vertical::Center,
+                    ..canvas::Text::default()
+                });
+            }
+        });
+
+        // Selection and focus from the list, plus hover, change too often to cache
+        let mut overlay = canvas::Frame::new(renderer, bounds.size());
+        for segment in segments.iter() {
+            let path = &self.tree.node(segment.node).info.path;
+            let color = if self.focused == Some(path.as_str()) {
+                Color::WHITE
+            } else if self.selected.contains(path) {
+                Color::from_rgb8(255, 215, 0)
+            } else {
+                continue;
+            };
+            overlay.stroke(&chart.arc_path(segment), canvas::Stroke::default().with_color(color).with_width(2.0));
+        }
+        if let (Some(hovered), Some(position)) = (Self::hovered(&segments, bounds, cursor), cursor.position_in(bounds)) {
+            let info = match hovered {
+                Some(segment) => {
+                    overlay.stroke(
+                        &chart.arc_path(&segment),
+                        canvas::Stroke::default().with_color(Color::WHITE).with_width(2.0),
+                    );
+                    &self.tree.node(segment.node).info
+                }
+                None => &root.info,
+            };
+            draw_node_tooltip(&mut overlay, bounds, position, info, self.unit);
+        }
+
+        vec![rings, overlay.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &SunburstState,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        let segments = self.segments(state);
+        if Self::hovered(&segments, bounds, cursor).is_some() {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+// Sunburst of the folder the tree cursor points at, next to that folder's list
+fn sunburst_results<'a>(app: &'a DiskViz, tree: &'a ScanTree) -> Element<'a, Message> {
+    let chart = canvas(Sunburst {
+        tree,
+        root: app.tree_cursor,
+        unit: app.config.unit,
+        selected: &app.selected,
+        focused: app.focused_entry.as_deref(),
+    })
+    .width(Length::Fill)
+    .height(Length::Fixed(SUNBURST_HEIGHT));
+
+    row![
+        column![
+            text("Click a ring to centre on that folder, the middle to go back up").size(12),
+            container(chart).style(ContainerStyle::Base).padding(5),
+        ]
+        .spacing(10)
+        .width(Length::FillPortion(2)),
+        container(tree_results(
+            tree,
+            app.tree_cursor,
+            app.current_sort,
+            &app.result_filter,
+            row_context(app),
+            app.list_scroll,
+        ))
+        .width(Length::FillPortion(3)),
+    ]
+    .spacing(15)
+    .into()
+}
@@ -0,0 +1,12 @@
+// Disk Maid's engine: settings, scanning, the result model and the actions that change the disk.
+// The GUI, command-line and terminal frontends in src/main.rs are built on top of it.
+
+pub mod actions;
+pub mod config;
+pub mod model;
+pub mod scanner;
+pub mod snapshot;
+
+pub use config::{load_config, save_config, AppConfig, Unit};
+pub use model::{sort_files, FileInfo, ScanResult, ScanTree, SortMethod};
+pub use scanner::{scan_directory, CancelToken, ScanLimits, ScanOptions, ScanProgress};
@@ -0,0 +1,50 @@
+#![cfg_attr(feature = "gui", windows_subsystem = "windows")]
+
+// The command line is always built; the GUI and the terminal browser are optional features
+mod cli;
+#[cfg(feature = "gui")]
+mod gui;
+#[cfg(feature = "tui")]
+mod tui;
+
+#[cfg(all(windows, feature = "gui"))]
+use cli::attach_parent_console;
+use cli::{run_cli, Cli};
+use disk_maid::config::load_config;
+
+pub fn main() {
+    #[cfg(all(windows, feature = "gui"))]
+    if std::env::args_os().len() > 1 {
+        attach_parent_console();
+    }
+
+    let cli = <Cli as clap::Parser>::parse();
+    match cli.command {
+        Some(command) => {
+            // Exit quietly when piped into `head` and friends instead of panicking on EPIPE
+            #[cfg(unix)]
+            unsafe {
+                libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+            }
+            let result = run_cli(command, load_config().unwrap_or_default(), &mut std::io::stdout().lock());
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        #[cfg(feature = "gui")]
+        None => {
+            use iced::Application;
+            if let Err(e) = gui::DiskViz::run(iced::Settings::default()) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        // Headless build: there is no window to open, so say what can be run instead
+        #[cfg(not(feature = "gui"))]
+        None => {
+            let _ = <Cli as clap::CommandFactory>::command().print_help();
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,999 @@
+// Plain data the scanner produces and every frontend shows: entries, sorting, filters, file types and the folder tree
+
+use crate::config::Unit;
+use std::{collections::HashMap, path::Path, time::SystemTime};
+
+// --- SCAN RESULTS ---
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileInfo {
+    pub path: String,
+    // For directories this is the total size of every file found below it
+    pub size: u64,
+    pub is_dir: bool,
+    pub modified: u64,
+    // Number of files below a directory (always 0 for files)
+    pub file_count: u64,
+    // Only filled in when the scan identified file contents
+    #[serde(default)]
+    pub content_type: Option<ContentType>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanResult {
+    pub root: String,
+    pub files: Vec<FileInfo>,
+    // Directories whose contents were not (fully) walked because a limit was reached
+    pub skipped_dirs: Vec<String>,
+    pub hit_depth_limit: bool,
+    pub hit_entry_limit: bool,
+    pub scope: ScanScope,
+}
+
+// The settings that decide which entries a scan records. Snapshots keep it so that two
+// scans recorded with different filters are not mistaken for changes on disk.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ScanScope {
+    // Filename patterns, exclusions prefixed with '!'
+    pub filter: Vec<String>,
+    pub exclude: Vec<String>,
+    // Result filter baked into the walk, if any
+    pub entry_filter: Option<ResultFilter>,
+}
+
+// One-line description of an entry shared by the list views and the command line
+pub fn size_label(file: &FileInfo, name: &str, unit: Unit) -> String {
+    if file.is_dir {
+        format!(
+            "[DIR] {:.2} {} ({} files) - {}",
+            unit.convert(file.size),
+            unit,
+            file.file_count,
+            name
+        )
+    } else if let Some(content) = file.content_type {
+        format!(
+            "{:.2} {} - {} [{}]",
+            unit.convert(file.size),
+            unit,
+            name,
+            content
+        )
+    } else {
+        format!(
+            "{:.2} {} - {}",
+            unit.convert(file.size),
+            unit,
+            name
+        )
+    }
+}
+
+// --- SORTING ---
+
+// Added Serialize/Deserialize here so we can save it to JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum SortMethod {
+    #[default]
+    NameAZ,
+    NameZA,
+    SizeLargest,
+    SizeSmallest,
+    Newest,
+    Oldest,
+}
+
+impl std::fmt::Display for SortMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SortMethod::NameAZ => "Name (A-Z)",
+                SortMethod::NameZA => "Name (Z-A)",
+                SortMethod::SizeLargest => "Size (Largest First)",
+                SortMethod::SizeSmallest => "Size (Smallest First)",
+                SortMethod::Newest => "Date (Newest First)",
+                SortMethod::Oldest => "Date (Oldest First)",
+            }
+        )
+    }
+}
+
+fn compare_files(a: &FileInfo, b: &FileInfo, method: SortMethod) -> std::cmp::Ordering {
+    match method {
+        SortMethod::NameAZ => a.path.to_lowercase().cmp(&b.path.to_lowercase()),
+        SortMethod::NameZA => b.path.to_lowercase().cmp(&a.path.to_lowercase()),
+        SortMethod::SizeLargest => b.size.cmp(&a.size),
+        SortMethod::SizeSmallest => a.size.cmp(&b.size),
+        SortMethod::Newest => b.modified.cmp(&a.modified),
+        SortMethod::Oldest => a.modified.cmp(&b.modified),
+    }
+}
+
+pub fn sort_files(files: &mut [FileInfo], method: SortMethod) {
+    files.sort_by(|a, b| compare_files(a, b, method));
+}
+
+// --- RESULT FILTERS ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum EntryKind {
+    #[default]
+    All,
+    FilesOnly,
+    DirsOnly,
+}
+
+impl EntryKind {
+    pub const ALL: [EntryKind; 3] = [EntryKind::All, EntryKind::FilesOnly, EntryKind::DirsOnly];
+}
+
+impl std::fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EntryKind::All => "Files & Folders",
+                EntryKind::FilesOnly => "Files Only",
+                EntryKind::DirsOnly => "Folders Only",
+            }
+        )
+    }
+}
+
+// File type criterion: a broad category or one format detected from content.
+// Folders never match a specific type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum TypeFilter {
+    #[default]
+    Any,
+    Category(FileCategory),
+    Content(ContentType),
+}
+
+impl TypeFilter {
+    pub fn choices() -> Vec<TypeFilter> {
+        std::iter::once(TypeFilter::Any)
+            .chain(FileCategory::ALL.into_iter().map(TypeFilter::Category))
+            .chain(ContentType::ALL.into_iter().map(TypeFilter::Content))
+            .collect()
+    }
+
+    pub fn matches(self, file: &FileInfo) -> bool {
+        match self {
+            TypeFilter::Any => true,
+            TypeFilter::Category(category) => !file.is_dir && file.category() == category,
+            TypeFilter::Content(content) => !file.is_dir && file.content_type == Some(content),
+        }
+    }
+}
+
+impl std::fmt::Display for TypeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeFilter::Any => write!(f, "Any Type"),
+            TypeFilter::Category(category) => write!(f, "{}", category),
+            TypeFilter::Content(content) => write!(f, "Detected: {}", content),
+        }
+    }
+}
+
+// Size, age, kind and type criteria applied on top of the filename filter
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ResultFilter {
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    // Unix timestamps (seconds)
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+    pub kind: EntryKind,
+    pub file_type: TypeFilter,
+}
+
+impl ResultFilter {
+    pub fn matches(&self, file: &FileInfo) -> bool {
+        self.matches_kind_and_date(file) && self.matches_size(file) && self.file_type.matches(file)
+    }
+
+    // Used while walking: directory sizes are only known after aggregation, so size
+    // bounds are checked against files alone, and a folders-only filter keeps every
+    // file for the totals. Content types are not known yet either; scan_directory
+    // applies the remaining criteria afterwards.
+    pub fn matches_during_scan(&self, file: &FileInfo) -> bool {
+        if file.is_dir {
+            self.matches_kind_and_date(file)
+        } else {
+            self.kind == EntryKind::DirsOnly || self.matches_kind_and_date(file) && self.matches_size(file)
+        }
+    }
+
+    // Second half of matches_during_scan, once directory totals are known
+    pub fn matches_after_aggregation(&self, file: &FileInfo) -> bool {
+        if file.is_dir {
+            self.matches_size(file)
+        } else {
+            self.kind != EntryKind::DirsOnly
+        }
+    }
+
+    fn matches_size(&self, file: &FileInfo) -> bool {
+        self.min_size.is_none_or(|min| file.size >= min) && self.max_size.is_none_or(|max| file.size <= max)
+    }
+
+    fn matches_kind_and_date(&self, file: &FileInfo) -> bool {
+        let kind_ok = match self.kind {
+            EntryKind::All => true,
+            EntryKind::FilesOnly => !file.is_dir,
+            EntryKind::DirsOnly => file.is_dir,
+        };
+        kind_ok
+            && self.modified_after.is_none_or(|after| file.modified >= after)
+            && self.modified_before.is_none_or(|before| file.modified < before)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.modified_after.is_some()
+            || self.modified_before.is_some()
+            || self.kind != EntryKind::All
+            || self.file_type != TypeFilter::Any
+    }
+}
+
+// Raw text of the filter inputs on the File & Scan screen
+#[derive(Debug, Clone, Default)]
+pub struct FilterInputs {
+    pub min_size: String,
+    pub max_size: String,
+    pub modified_after: String,
+    pub modified_before: String,
+    pub kind: EntryKind,
+    pub file_type: TypeFilter,
+}
+
+impl FilterInputs {
+    // Sizes are entered in the display unit
+    pub fn parse(&self, unit: Unit) -> Result<ResultFilter, String> {
+        let size = |label: &str, value: &str| -> Result<Option<u64>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            match value.parse::<f64>() {
+                Ok(v) if v >= 0.0 => Ok(Some(unit.to_bytes(v))),
+                _ => Err(format!("{} must be a positive number of {}", label, unit)),
+            }
+        };
+        let date = |label: &str, value: &str| -> Result<Option<u64>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            parse_date(value)
+                .map(Some)
+                .ok_or_else(|| format!("{} must be a date (YYYY-MM-DD) or an age like 365d", label))
+        };
+
+        Ok(ResultFilter {
+            min_size: size("Min size", &self.min_size)?,
+            max_size: size("Max size", &self.max_size)?,
+            modified_after: date("Modified after", &self.modified_after)?,
+            modified_before: date("Modified before", &self.modified_before)?,
+            kind: self.kind,
+            file_type: self.file_type,
+        })
+    }
+}
+
+// Accepts "YYYY-MM-DD" (UTC midnight) or "<N>d" meaning N days ago
+fn parse_date(value: &str) -> Option<u64> {
+    if let Some(days) = value.strip_suffix('d') {
+        let days: u64 = days.trim().parse().ok()?;
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).ok()?.as_secs();
+        return Some(now.saturating_sub(days.checked_mul(86_400)?));
+    }
+
+    let mut parts = value.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+
+    // Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    // Absurd years would overflow; treat them like any other invalid date
+    let days = era.checked_mul(146_097)?.checked_add(doe - 719_468)?;
+    u64::try_from(days.checked_mul(86_400)?).ok()
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// --- FILE TYPES ---
+
+// Broad kind of data a file holds, guessed from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum FileCategory {
+    Video,
+    Image,
+    Audio,
+    Archive,
+    Document,
+    Source,
+    Binary,
+    Other,
+}
+
+impl FileCategory {
+    pub const ALL: [FileCategory; 8] = [
+        FileCategory::Video,
+        FileCategory::Image,
+        FileCategory::Audio,
+        FileCategory::Archive,
+        FileCategory::Document,
+        FileCategory::Source,
+        FileCategory::Binary,
+        FileCategory::Other,
+    ];
+
+    pub fn from_path(path: &Path) -> FileCategory {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "mp4" | "mkv" | "avi" | "mov" | "wmv" | "webm" | "flv" | "m4v" | "mpg" | "mpeg" | "m2ts" | "3gp" => {
+                FileCategory::Video
+            }
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "tif" | "tiff" | "svg" | "heic" | "ico" | "psd"
+            | "raw" | "cr2" | "nef" | "dng" => FileCategory::Image,
+            "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "opus" | "wma" | "aiff" => FileCategory::Audio,
+            "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "7z" | "rar" | "iso" | "dmg" | "cab" => {
+                FileCategory::Archive
+            }
+            "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "rtf" | "txt"
+            | "md" | "epub" | "csv" => FileCategory::Document,
+            "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "py" | "js" | "ts" | "jsx" | "tsx" | "java" | "kt" | "go"
+            | "rb" | "php" | "cs" | "swift" | "sh" | "ps1" | "html" | "css" | "json" | "toml" | "yaml" | "yml"
+            | "xml" | "sql" | "lua" => FileCategory::Source,
+            "exe" | "dll" | "so" | "dylib" | "a" | "lib" | "o" | "obj" | "bin" | "msi" | "deb" | "rpm" | "apk"
+            | "class" | "jar" | "wasm" | "pyc" => FileCategory::Binary,
+            _ => FileCategory::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for FileCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FileCategory::Video => "Video",
+                FileCategory::Image => "Images",
+                FileCategory::Audio => "Audio",
+                FileCategory::Archive => "Archives",
+                FileCategory::Document => "Documents",
+                FileCategory::Source => "Source code",
+                FileCategory::Binary => "Binaries",
+                FileCategory::Other => "Other",
+            }
+        )
+    }
+}
+
+// Real format of a file as told by its first bytes, independent of its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ContentType {
+    Elf,
+    WindowsExecutable,
+    MachO,
+    WebAssembly,
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Tiff,
+    Pdf,
+    Zip,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+    SevenZip,
+    Rar,
+    Tar,
+    Sqlite,
+    IsoMedia,
+    Matroska,
+    Avi,
+    Mp3,
+    Flac,
+    Ogg,
+    Wav,
+}
+
+impl ContentType {
+    pub const ALL: [ContentType; 26] = [
+        ContentType::Elf,
+        ContentType::WindowsExecutable,
+        ContentType::MachO,
+        ContentType::WebAssembly,
+        ContentType::Png,
+        ContentType::Jpeg,
+        ContentType::Gif,
+        ContentType::Webp,
+        ContentType::Tiff,
+        ContentType::Pdf,
+        ContentType::Zip,
+        ContentType::Gzip,
+        ContentType::Bzip2,
+        ContentType::Xz,
+        ContentType::Zstd,
+        ContentType::SevenZip,
+        ContentType::Rar,
+        ContentType::Tar,
+        ContentType::Sqlite,
+        ContentType::IsoMedia,
+        ContentType::Matroska,
+        ContentType::Avi,
+        ContentType::Mp3,
+        ContentType::Flac,
+        ContentType::Ogg,
+        ContentType::Wav,
+    ];
+
+    pub fn detect(header: &[u8]) -> Option<ContentType> {
+        let starts = |magic: &[u8]| header.starts_with(magic);
+        let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+        let riff = |kind: &[u8]| starts(b"RIFF") && at(8, kind);
+
+        let detected = if starts(b"\x7fELF") {
+            ContentType::Elf
+        } else if starts(b"MZ") {
+            ContentType::WindowsExecutable
+        } else if [b"\xcf\xfa\xed\xfe", b"\xce\xfa\xed\xfe", b"\xfe\xed\xfa\xcf", b"\xfe\xed\xfa\xce"]
+            .iter()
+            .any(|magic| starts(&magic[..]))
+        {
+            ContentType::MachO
+        } else if starts(b"\0asm") {
+            ContentType::WebAssembly
+        } else if starts(b"\x89PNG\r\n\x1a\n") {
+            ContentType::Png
+        } else if starts(b"\xff\xd8\xff") {
+            ContentType::Jpeg
+        } else if starts(b"GIF87a") || starts(b"GIF89a") {
+            ContentType::Gif
+        } else if riff(b"WEBP") {
+            ContentType::Webp
+        } else if riff(b"WAVE") {
+            ContentType::Wav
+        } else if riff(b"AVI ") {
+            ContentType::Avi
+        } else if starts(b"II*\0") || starts(b"MM\0*") {
+            ContentType::Tiff
+        } else if starts(b"%PDF-") {
+            ContentType::Pdf
+        } else if starts(b"PK\x03\x04") || starts(b"PK\x05\x06") {
+            ContentType::Zip
+        } else if starts(b"\x1f\x8b") {
+            ContentType::Gzip
+        } else if starts(b"BZh") {
+            ContentType::Bzip2
+        } else if starts(b"\xfd7zXZ\0") {
+            ContentType::Xz
+        } else if starts(b"\x28\xb5\x2f\xfd") {
+            ContentType::Zstd
+        } else if starts(b"7z\xbc\xaf\x27\x1c") {
+            ContentType::SevenZip
+        } else if starts(b"Rar!\x1a\x07") {
+            ContentType::Rar
+        } else if at(257, b"ustar") {
+            ContentType::Tar
+        } else if starts(b"SQLite format 3\0") {
+            ContentType::Sqlite
+        } else if at(4, b"ftyp") {
+            ContentType::IsoMedia
+        } else if starts(b"\x1a\x45\xdf\xa3") {
+            ContentType::Matroska
+        } else if starts(b"ID3") {
+            ContentType::Mp3
+        } else if starts(b"fLaC") {
+            ContentType::Flac
+        } else if starts(b"OggS") {
+            ContentType::Ogg
+        } else {
+            return None;
+        };
+        Some(detected)
+    }
+
+    pub fn category(self) -> FileCategory {
+        match self {
+            ContentType::Elf | ContentType::WindowsExecutable | ContentType::MachO | ContentType::WebAssembly => {
+                FileCategory::Binary
+            }
+            ContentType::Png | ContentType::Jpeg | ContentType::Gif | ContentType::Webp | ContentType::Tiff => {
+                FileCategory::Image
+            }
+            ContentType::Pdf => FileCategory::Document,
+            ContentType::Zip
+            | ContentType::Gzip
+            | ContentType::Bzip2
+            | ContentType::Xz
+            | ContentType::Zstd
+            | ContentType::SevenZip
+            | ContentType::Rar
+            | ContentType::Tar => FileCategory::Archive,
+            ContentType::Sqlite => FileCategory::Other,
+            ContentType::IsoMedia | ContentType::Matroska | ContentType::Avi => FileCategory::Video,
+            ContentType::Mp3 | ContentType::Flac | ContentType::Ogg | ContentType::Wav => FileCategory::Audio,
+        }
+    }
+
+    // Formats other formats are built on (.docx and .jar are zips, .m4a and .heic are ISO media),
+    // so a specific extension says more than the magic bytes
+    fn is_container(self) -> bool {
+        matches!(self, ContentType::Zip | ContentType::IsoMedia | ContentType::Ogg)
+    }
+}
+
+impl std::fmt::Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ContentType::Elf => "ELF",
+                ContentType::WindowsExecutable => "Windows executable",
+                ContentType::MachO => "Mach-O",
+                ContentType::WebAssembly => "WebAssembly",
+                ContentType::Png => "PNG",
+                ContentType::Jpeg => "JPEG",
+                ContentType::Gif => "GIF",
+                ContentType::Webp => "WebP",
+                ContentType::Tiff => "TIFF",
+                ContentType::Pdf => "PDF",
+                ContentType::Zip => "ZIP",
+                ContentType::Gzip => "gzip",
+                ContentType::Bzip2 => "bzip2",
+                ContentType::Xz => "xz",
+                ContentType::Zstd => "Zstandard",
+                ContentType::SevenZip => "7-Zip",
+                ContentType::Rar => "RAR",
+                ContentType::Tar => "tar",
+                ContentType::Sqlite => "SQLite",
+                ContentType::IsoMedia => "MP4/QuickTime",
+                ContentType::Matroska => "Matroska",
+                ContentType::Avi => "AVI",
+                ContentType::Mp3 => "MP3",
+                ContentType::Flac => "FLAC",
+                ContentType::Ogg => "Ogg",
+                ContentType::Wav => "WAV",
+            }
+        )
+    }
+}
+
+impl FileInfo {
+    // Detected content wins over the extension, except for generic containers
+    pub fn category(&self) -> FileCategory {
+        let by_name = FileCategory::from_path(Path::new(&self.path));
+        match self.content_type {
+            Some(content) if content.is_container() && by_name != FileCategory::Other => by_name,
+            Some(content) => content.category(),
+            None => by_name,
+        }
+    }
+
+    // Lower-cased extension, used to group files in the type breakdown
+    pub fn extension(&self) -> String {
+        Path::new(&self.path)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "(none)".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypeStats {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+impl TypeStats {
+    fn add(&mut self, file: &FileInfo) {
+        self.files += 1;
+        self.bytes += file.size;
+    }
+
+    // Share of `total` bytes, in percent
+    pub fn percent_of(&self, total: &TypeStats) -> f32 {
+        if total.bytes == 0 {
+            0.0
+        } else {
+            (self.bytes as f64 / total.bytes as f64 * 100.0) as f32
+        }
+    }
+}
+
+// Where the bytes of a scan go, by category, extension and detected content (largest first)
+#[derive(Debug, Clone, Default)]
+pub struct TypeBreakdown {
+    pub total: TypeStats,
+    pub categories: Vec<(FileCategory, TypeStats)>,
+    pub extensions: Vec<(String, FileCategory, TypeStats)>,
+    // Empty unless the scan identified file contents
+    pub content_types: Vec<(ContentType, TypeStats)>,
+}
+
+pub fn type_breakdown(files: &[FileInfo]) -> TypeBreakdown {
+    let mut total = TypeStats::default();
+    let mut categories: HashMap<FileCategory, TypeStats> = HashMap::new();
+    let mut extensions: HashMap<String, (FileCategory, TypeStats)> = HashMap::new();
+    let mut content_types: HashMap<ContentType, TypeStats> = HashMap::new();
+
+    for file in files.iter().filter(|f| !f.is_dir) {
+        if let Some(content) = file.content_type {
+            content_types.entry(content).or_default().add(file);
+        }
+        let category = file.category();
+        total.add(file);
+        categories.entry(category).or_default().add(file);
+        extensions
+            .entry(file.extension())
+            .or_insert_with(|| (FileCategory::from_path(Path::new(&file.path)), TypeStats::default()))
+            .1
+            .add(file);
+    }
+
+    let mut categories: Vec<(FileCategory, TypeStats)> = categories.into_iter().collect();
+    categories.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.bytes));
+    let mut extensions: Vec<(String, FileCategory, TypeStats)> = extensions
+        .into_iter()
+        .map(|(extension, (category, stats))| (extension, category, stats))
+        .collect();
+    extensions.sort_by_key(|(_, _, stats)| std::cmp::Reverse(stats.bytes));
+    let mut content_types: Vec<(ContentType, TypeStats)> = content_types.into_iter().collect();
+    content_types.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.bytes));
+
+    TypeBreakdown { total, categories, extensions, content_types }
+}
+
+// --- TREE MODEL ---
+
+#[derive(Debug, Clone)]
+pub struct TreeNode {
+    pub name: String,
+    // Sizes and counts are cumulative for directories
+    pub info: FileInfo,
+    pub dir_count: u64,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+// Directory hierarchy of a scan, stored as an arena with the scanned root at index 0
+#[derive(Debug, Clone)]
+pub struct ScanTree {
+    pub nodes: Vec<TreeNode>,
+    index: HashMap<String, usize>,
+}
+
+impl ScanTree {
+    pub const ROOT: usize = 0;
+
+    pub fn build(root: &str, files: &[FileInfo]) -> Self {
+        let root_node = TreeNode {
+            name: root.to_string(),
+            info: FileInfo {
+                path: root.to_string(),
+                size: 0,
+                is_dir: true,
+                modified: 0,
+                file_count: 0,
+                content_type: None,
+            },
+            dir_count: 0,
+            parent: None,
+            children: Vec::new(),
+        };
+        let mut tree = ScanTree {
+            nodes: vec![root_node],
+            index: HashMap::new(),
+        };
+        tree.index.insert(root.to_string(), Self::ROOT);
+
+        for file in files {
+            let name = Path::new(&file.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.path.clone());
+            tree.index.insert(file.path.clone(), tree.nodes.len());
+            tree.nodes.push(TreeNode {
+                name,
+                info: file.clone(),
+                dir_count: 0,
+                parent: None,
+                children: Vec::new(),
+            });
+        }
+
+        // Link every node to its closest recorded ancestor, skipping directories a filter
+        // dropped; anything outside the root hangs off the root
+        for id in 1..tree.nodes.len() {
+            let parent = Path::new(&tree.nodes[id].info.path)
+                .ancestors()
+                .skip(1)
+                .find_map(|p| tree.index.get(p.to_string_lossy().as_ref()).copied())
+                .filter(|&p| p != id)
+                .unwrap_or(Self::ROOT);
+            tree.nodes[id].parent = Some(parent);
+            tree.nodes[parent].children.push(id);
+        }
+
+        // Directories already carry their du-style totals from the scan, which stay
+        // right even when a filter dropped the files below them; only the root is summed
+        for id in 1..tree.nodes.len() {
+            let info = &tree.nodes[id].info;
+            let (size, files) = (info.size, if info.is_dir { info.file_count } else { 1 });
+            if tree.nodes[id].parent == Some(Self::ROOT) {
+                let root = &mut tree.nodes[Self::ROOT].info;
+                root.size += size;
+                root.file_count += files;
+            }
+            if tree.nodes[id].info.is_dir {
+                let mut current = tree.nodes[id].parent;
+                while let Some(ancestor) = current {
+                    tree.nodes[ancestor].dir_count += 1;
+                    current = tree.nodes[ancestor].parent;
+                }
+            }
+        }
+
+        tree
+    }
+
+    pub fn node(&self, id: usize) -> &TreeNode {
+        &self.nodes[id]
+    }
+
+    pub fn find(&self, path: &str) -> Option<usize> {
+        self.index.get(path).copied()
+    }
+
+    pub fn sorted_children(&self, id: usize, method: SortMethod) -> Vec<usize> {
+        let mut children = self.nodes[id].children.clone();
+        children.sort_by(|&a, &b| compare_files(&self.nodes[a].info, &self.nodes[b].info, method));
+        children
+    }
+
+    // Path from the root down to `id`, used for the breadcrumb bar
+    pub fn breadcrumb(&self, id: usize) -> Vec<usize> {
+        let mut trail = vec![id];
+        let mut current = self.nodes[id].parent;
+        while let Some(parent) = current {
+            trail.push(parent);
+            current = self.nodes[parent].parent;
+        }
+        trail.reverse();
+        trail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, is_dir: bool, size: u64, modified: u64) -> FileInfo {
+        FileInfo {
+            path: path.to_string(),
+            size,
+            is_dir,
+            modified,
+            file_count: 0,
+            content_type: None,
+        }
+    }
+
+    #[test]
+    fn parse_date_reads_calendar_dates_as_utc_midnight() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(951_868_800));
+        assert_eq!(parse_date("2024-02-29"), Some(1_709_164_800));
+        assert_eq!(parse_date("2000-02-29"), Some(951_782_400));
+    }
+
+    #[test]
+    fn parse_date_rejects_malformed_dates() {
+        let invalid = [
+            "",
+            "2024",
+            "2024-13-01",
+            "2024-00-10",
+            "2024-01-32",
+            "2024-02-31",
+            "2023-02-29",
+            "1900-02-29",
+            "2024-04-31",
+            "2024-01-01-01",
+            "yesterday",
+            "1969-12-31",
+        ];
+        for value in invalid {
+            assert_eq!(parse_date(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn parse_date_reads_ages_in_days() {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        let week_ago = parse_date("7d").unwrap();
+        assert!(now - week_ago >= 7 * 86_400 && now - week_ago < 7 * 86_400 + 5);
+        assert_eq!(parse_date("0d").map(|t| t.abs_diff(now) < 5), Some(true));
+    }
+
+    #[test]
+    fn parse_date_does_not_overflow_on_huge_input() {
+        assert_eq!(parse_date("99999999999999999d"), None);
+        assert_eq!(parse_date("999999999999999999-01-01"), None);
+    }
+
+    #[test]
+    fn parse_date_round_trips_through_format_timestamp() {
+        for date in ["1970-01-01", "1999-12-31", "2000-02-29", "2024-02-29", "2100-03-01"] {
+            let secs = parse_date(date).unwrap();
+            assert_eq!(crate::actions::format_timestamp(secs), format!("{}T00:00:00Z", date));
+        }
+    }
+
+    #[test]
+    fn filter_inputs_report_which_field_is_wrong() {
+        let inputs = FilterInputs {
+            min_size: "-1".into(),
+            ..Default::default()
+        };
+        assert!(inputs.parse(Unit::MB).unwrap_err().starts_with("Min size"));
+
+        let inputs = FilterInputs {
+            modified_before: "soon".into(),
+            ..Default::default()
+        };
+        assert!(inputs.parse(Unit::MB).unwrap_err().starts_with("Modified before"));
+
+        let filter = FilterInputs::default().parse(Unit::MB).unwrap();
+        assert!(!filter.is_active());
+    }
+
+    #[test]
+    fn result_filter_checks_size_date_and_kind() {
+        let filter = ResultFilter {
+            min_size: Some(100),
+            modified_after: Some(1_000),
+            kind: EntryKind::FilesOnly,
+            ..Default::default()
+        };
+        assert!(filter.matches(&entry("/a/big", false, 500, 2_000)));
+        assert!(!filter.matches(&entry("/a/small", false, 50, 2_000)));
+        assert!(!filter.matches(&entry("/a/old", false, 500, 500)));
+        assert!(!filter.matches(&entry("/a", true, 500, 2_000)));
+    }
+
+    #[test]
+    fn size_bounds_are_not_applied_to_directories_during_a_scan() {
+        let filter = ResultFilter {
+            min_size: Some(100),
+            ..Default::default()
+        };
+        // Directory sizes are still 0 while walking
+        assert!(filter.matches_during_scan(&entry("/a", true, 0, 0)));
+        assert!(!filter.matches_during_scan(&entry("/a/small", false, 50, 0)));
+    }
+
+    #[test]
+    fn detect_recognises_magic_numbers() {
+        let cases: [(&[u8], ContentType); 8] = [
+            (b"\x7fELF\x02\x01\x01", ContentType::Elf),
+            (b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", ContentType::Png),
+            (b"\xff\xd8\xff\xe0", ContentType::Jpeg),
+            (b"RIFF\x24\0\0\0WAVEfmt ", ContentType::Wav),
+            (b"RIFF\x24\0\0\0WEBPVP8 ", ContentType::Webp),
+            (b"%PDF-1.7", ContentType::Pdf),
+            (b"PK\x03\x04\x14\0", ContentType::Zip),
+            (b"\0\0\0\x20ftypisom", ContentType::IsoMedia),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(ContentType::detect(header), Some(expected));
+        }
+    }
+
+    #[test]
+    fn detect_finds_tar_magic_at_its_offset() {
+        let mut header = vec![0u8; 512];
+        header[257..262].copy_from_slice(b"ustar");
+        assert_eq!(ContentType::detect(&header), Some(ContentType::Tar));
+        // Too short to hold the magic
+        assert_eq!(ContentType::detect(&header[..260]), None);
+    }
+
+    #[test]
+    fn detect_gives_up_on_unknown_or_empty_content() {
+        assert_eq!(ContentType::detect(b""), None);
+        assert_eq!(ContentType::detect(b"RIFF"), None);
+        assert_eq!(ContentType::detect(b"just some text"), None);
+    }
+
+    #[test]
+    fn detected_content_overrides_the_extension_except_for_containers() {
+        let mut file = entry("/data/picture.txt", false, 1, 0);
+        file.content_type = Some(ContentType::Png);
+        assert_eq!(file.category(), FileCategory::Image);
+
+        // A .docx is a zip, but the extension says more
+        let mut file = entry("/data/report.docx", false, 1, 0);
+        file.content_type = Some(ContentType::Zip);
+        assert_eq!(file.category(), FileCategory::Document);
+
+        let mut file = entry("/data/download", false, 1, 0);
+        file.content_type = Some(ContentType::Zip);
+        assert_eq!(file.category(), FileCategory::Archive);
+    }
+
+    #[test]
+    fn tree_links_entries_past_directories_a_filter_dropped() {
+        // "/root/a/b" was filtered out, its file belongs under "/root/a"
+        let mut a = entry("/root/a", true, 10_240, 0);
+        a.file_count = 1;
+        let files = vec![a, entry("/root/a/b/big.bin", false, 10_240, 0)];
+        let tree = ScanTree::build("/root", &files);
+        let a = tree.find("/root/a").unwrap();
+        let big = tree.find("/root/a/b/big.bin").unwrap();
+        assert_eq!(tree.node(big).parent, Some(a));
+        assert_eq!(tree.node(a).info.size, 10_240);
+        assert_eq!(tree.node(ScanTree::ROOT).info.size, 10_240);
+        assert_eq!(tree.breadcrumb(big), vec![ScanTree::ROOT, a, big]);
+    }
+
+    #[test]
+    fn tree_keeps_folder_totals_without_files() {
+        // What a folders-only scan returns: totals, but no files below them
+        let mut a = entry("/root/a", true, 300, 0);
+        a.file_count = 3;
+        let mut b = entry("/root/a/b", true, 100, 0);
+        b.file_count = 1;
+        let tree = ScanTree::build("/root", &[a, b]);
+        let root = tree.node(ScanTree::ROOT);
+        assert_eq!((root.info.size, root.info.file_count, root.dir_count), (300, 3, 2));
+        assert_eq!(tree.node(tree.find("/root/a/b").unwrap()).info.size, 100);
+    }
+
+    #[test]
+    fn folders_only_keeps_files_until_the_totals_are_known() {
+        let filter = ResultFilter {
+            kind: EntryKind::DirsOnly,
+            min_size: Some(100),
+            ..Default::default()
+        };
+        let small_file = entry("/a/small", false, 10, 0);
+        assert!(filter.matches_during_scan(&small_file));
+        assert!(!filter.matches_after_aggregation(&small_file));
+        assert!(filter.matches_after_aggregation(&entry("/a", true, 500, 0)));
+        assert!(!filter.matches_after_aggregation(&entry("/b", true, 50, 0)));
+    }
+}
@@ -0,0 +1,1160 @@
+// Directory walker, content detection and the duplicate finder
+
+use crate::model::{ContentType, FileInfo, ResultFilter, ScanResult, ScanScope, TypeFilter};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+// --- SCAN OPTIONS ---
+
+// How far the scanner is allowed to go; `None` means no limit
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanLimits {
+    pub max_depth: Option<usize>,
+    pub max_entries: Option<usize>,
+}
+
+// Everything the walker needs to know besides the root path
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub filter: FilterSet,
+    pub exclude: ExcludeSet,
+    // Size/date/kind criteria baked into the walk; excluded entries are not recorded
+    pub entry_filter: ResultFilter,
+    pub limits: ScanLimits,
+    pub threads: usize,
+    // Read the first bytes of every file to identify its real type
+    pub detect_content: bool,
+}
+
+impl ScanOptions {
+    pub fn scope(&self) -> ScanScope {
+        let filter = self.filter.include.iter().map(|p| p.as_str().to_string());
+        let negated = self.filter.exclude.iter().map(|p| format!("!{}", p.as_str()));
+        ScanScope {
+            filter: filter.chain(negated).collect(),
+            exclude: self.exclude.patterns.iter().map(|p| p.as_str().to_string()).collect(),
+            entry_filter: self.entry_filter.is_active().then(|| self.entry_filter.clone()),
+        }
+    }
+}
+
+// Live counters reported by the scanner while it walks the tree
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub files_found: usize,
+    pub dirs_found: usize,
+    pub bytes_counted: u64,
+    pub current_dir: String,
+    pub elapsed: Duration,
+}
+
+// Shared flag the UI flips to stop a running scan
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// How often the scanner sends a progress batch to the UI
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// Accumulates progress during a scan and forwards it at most once per PROGRESS_INTERVAL
+struct ProgressReporter<'a> {
+    progress: ScanProgress,
+    started: Instant,
+    last_report: Instant,
+    callback: &'a mut dyn FnMut(&ScanProgress),
+}
+
+impl<'a> ProgressReporter<'a> {
+    fn new(callback: &'a mut dyn FnMut(&ScanProgress)) -> Self {
+        let now = Instant::now();
+        Self {
+            progress: ScanProgress::default(),
+            started: now,
+            last_report: now,
+            callback,
+        }
+    }
+
+    fn enter_dir(&mut self, dir: &Path) {
+        self.progress.current_dir = dir.to_string_lossy().to_string();
+        self.tick();
+    }
+
+    fn found_dir(&mut self) {
+        self.progress.dirs_found += 1;
+    }
+
+    fn found_file(&mut self, size: u64) {
+        self.progress.files_found += 1;
+        self.progress.bytes_counted += size;
+        self.tick();
+    }
+
+    fn tick(&mut self) {
+        if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            self.report();
+        }
+    }
+
+    fn report(&mut self) {
+        self.last_report = Instant::now();
+        self.progress.elapsed = self.started.elapsed();
+        (self.callback)(&self.progress);
+    }
+}
+
+// --- FILENAME FILTERS ---
+
+// Parsed form of `AppConfig::scan_filter`: comma or semicolon separated globs,
+// `{a,b}` alternatives and `!pattern` to exclude. Empty means "everything".
+#[derive(Debug, Clone, Default)]
+pub struct FilterSet {
+    include: Vec<glob::Pattern>,
+    exclude: Vec<glob::Pattern>,
+}
+
+const FILTER_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl FilterSet {
+    pub fn parse(spec: &str) -> Result<FilterSet, String> {
+        let mut set = FilterSet::default();
+
+        for raw in split_top_level(spec, &[',', ';']) {
+            let raw = raw.trim();
+            if raw.is_empty() {
+                continue;
+            }
+            let (negated, pattern) = match raw.strip_prefix('!') {
+                Some(rest) => (true, rest.trim()),
+                None => (false, raw),
+            };
+            // "*.*" traditionally means "every file", even ones without an extension
+            let pattern = if pattern == "*.*" { "*" } else { pattern };
+
+            let compiled = compile_pattern(pattern)?;
+            if negated {
+                set.exclude.extend(compiled);
+            } else {
+                set.include.extend(compiled);
+            }
+        }
+
+        Ok(set)
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        (self.include.is_empty() || any_pattern_matches(&self.include, path))
+            && !any_pattern_matches(&self.exclude, path)
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.include.len(), self.exclude.len()) {
+            (0, 0) => "Matches every file".to_string(),
+            (include, 0) => format!("{} include pattern(s)", include),
+            (0, exclude) => format!("Every file except {} exclude pattern(s)", exclude),
+            (include, exclude) => format!("{} include, {} exclude pattern(s)", include, exclude),
+        }
+    }
+}
+
+// Directories and files the walker skips entirely; excluded directories are never descended
+#[derive(Debug, Clone, Default)]
+pub struct ExcludeSet {
+    patterns: Vec<glob::Pattern>,
+}
+
+impl ExcludeSet {
+    pub fn new(patterns: &[String]) -> Result<ExcludeSet, String> {
+        let mut set = ExcludeSet::default();
+        for raw in patterns {
+            set.patterns.extend(compile_pattern(raw.trim())?);
+        }
+        Ok(set)
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        any_pattern_matches(&self.patterns, path)
+    }
+}
+
+pub fn compile_pattern(raw: &str) -> Result<Vec<glob::Pattern>, String> {
+    expand_braces(raw)?
+        .iter()
+        .map(|p| glob::Pattern::new(p).map_err(|e| format!("'{}': {}", raw, e.msg)))
+        .collect()
+}
+
+// Patterns containing a path separator are matched against the full path,
+// everything else against the file name only
+fn any_pattern_matches(patterns: &[glob::Pattern], path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let full = path.to_string_lossy();
+    patterns.iter().any(|pattern| {
+        let target = if pattern.as_str().contains(['/', '\\']) { &full } else { &name };
+        pattern.matches_with(target, FILTER_MATCH_OPTIONS)
+    })
+}
+
+// Split on any of `separators`, ignoring those inside `{...}` or `[...]`
+fn split_top_level<'a>(spec: &'a str, separators: &[char]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut braces = 0usize;
+    let mut in_class = false;
+    let mut start = 0;
+
+    for (i, c) in spec.char_indices() {
+        match c {
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            '{' if !in_class => braces += 1,
+            '}' if !in_class => braces = braces.saturating_sub(1),
+            c if braces == 0 && !in_class && separators.contains(&c) => {
+                parts.push(&spec[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&spec[start..]);
+    parts
+}
+
+// "*.{jpg,png}" -> ["*.jpg", "*.png"], nested groups included
+fn expand_braces(pattern: &str) -> Result<Vec<String>, String> {
+    let Some(open) = pattern.find('{') else {
+        if pattern.contains('}') {
+            return Err(format!("'{}': unmatched '}}'", pattern));
+        }
+        return Ok(vec![pattern.to_string()]);
+    };
+
+    let mut depth = 0usize;
+    let mut close = None;
+    for (i, c) in pattern[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close.ok_or_else(|| format!("'{}': unmatched '{{'", pattern))?;
+
+    let prefix = &pattern[..open];
+    let suffix = &pattern[close + 1..];
+    let mut expanded = Vec::new();
+    for alternative in split_top_level(&pattern[open + 1..close], &[',']) {
+        expanded.extend(expand_braces(&format!("{}{}{}", prefix, alternative, suffix))?);
+    }
+    Ok(expanded)
+}
+
+// --- WALKER ---
+
+pub fn scan_directory(
+    path: PathBuf,
+    options: &ScanOptions,
+    cancel: &CancelToken,
+    mut on_progress: impl FnMut(&ScanProgress),
+) -> Result<ScanResult, String> {
+    let started = Instant::now();
+    let mut result = if options.threads == 1 {
+        scan_sequential(&path, options, cancel, &mut on_progress)?
+    } else {
+        scan_parallel(&path, options, cancel, &mut on_progress)?
+    };
+
+    // A baked-in detected-type filter needs the detection pass even if it is switched off
+    let file_type = options.entry_filter.file_type;
+    if options.detect_content || matches!(file_type, TypeFilter::Content(_)) {
+        let mut progress = ScanProgress {
+            files_found: result.files.iter().filter(|f| !f.is_dir).count(),
+            dirs_found: result.files.iter().filter(|f| f.is_dir).count(),
+            bytes_counted: result.files.iter().filter(|f| !f.is_dir).map(|f| f.size).sum(),
+            ..ScanProgress::default()
+        };
+        detect_content_types(&mut result.files, options.threads, cancel, |done, total| {
+            progress.current_dir = format!("Identifying file types... {}/{}", done, total);
+            progress.elapsed = started.elapsed();
+            on_progress(&progress);
+        })?;
+    }
+    if file_type != TypeFilter::Any {
+        result.files.retain(|f| f.is_dir || file_type.matches(f));
+    }
+
+    aggregate_directory_sizes(&mut result.files);
+    let filter = &options.entry_filter;
+    result.files.retain(|f| filter.matches_after_aggregation(f));
+    result.scope = options.scope();
+    Ok(result)
+}
+
+fn file_info(path: &Path, metadata: &fs::Metadata) -> FileInfo {
+    let modified = metadata.modified()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    FileInfo {
+        path: path.to_string_lossy().to_string(),
+        // Directory sizes are filled in afterwards by aggregate_directory_sizes
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        is_dir: metadata.is_dir(),
+        modified,
+        file_count: 0,
+        content_type: None,
+    }
+}
+
+// Depth-first walk on the calling thread
+fn scan_sequential(
+    path: &Path,
+    options: &ScanOptions,
+    cancel: &CancelToken,
+    on_progress: &mut dyn FnMut(&ScanProgress),
+) -> Result<ScanResult, String> {
+    struct Walk<'a> {
+        options: &'a ScanOptions,
+        cancel: &'a CancelToken,
+        reporter: ProgressReporter<'a>,
+        result: ScanResult,
+    }
+
+    impl Walk<'_> {
+        fn scan_recursive(&mut self, dir: &Path, depth: usize) -> Result<(), String> {
+            let entries = match fs::read_dir(dir) {
+                Ok(e) => e,
+                Err(_) => return Ok(()),
+            };
+            self.reporter.enter_dir(dir);
+            let limits = self.options.limits;
+            let mut recorded_here = 0;
+
+            for entry in entries.flatten() {
+                if self.cancel.is_cancelled() {
+                    return Err("Scan cancelled".into());
+                }
+
+                let path = entry.path();
+                if self.options.exclude.matches(&path) {
+                    continue;
+                }
+                // Does not follow symlinks: a link is recorded as a plain entry and never descended
+                let metadata = match entry.metadata() {
+                    Ok(m) => m,
+                    Err(_) => continue,
+                };
+                if !metadata.is_dir() && !self.options.filter.matches(&path) {
+                    continue;
+                }
+                let info = file_info(&path, &metadata);
+                let record = self.options.entry_filter.matches_during_scan(&info);
+                if !record && !info.is_dir {
+                    continue;
+                }
+                // Same rule as scan_parallel: stop at the first entry past the limit, and
+                // list the folder as skipped only if none of its own entries made it in
+                if limits.max_entries.is_some_and(|max| self.result.files.len() >= max) {
+                    self.result.hit_entry_limit = true;
+                    if recorded_here == 0 {
+                        self.result.skipped_dirs.push(dir.to_string_lossy().to_string());
+                    }
+                    return Ok(());
+                }
+
+                if info.is_dir {
+                    let path_str = info.path.clone();
+                    if record {
+                        self.result.files.push(info);
+                        recorded_here += 1;
+                    }
+                    self.reporter.found_dir();
+
+                    if limits.max_depth.is_some_and(|max| depth + 1 > max) {
+                        self.result.hit_depth_limit = true;
+                        self.result.skipped_dirs.push(path_str);
+                    } else {
+                        self.scan_recursive(&path, depth + 1)?;
+                    }
+                } else {
+                    self.result.files.push(info);
+                    recorded_here += 1;
+                    self.reporter.found_file(metadata.len());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let mut walk = Walk {
+        options,
+        cancel,
+        reporter: ProgressReporter::new(on_progress),
+        result: ScanResult {
+            root: path.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+    };
+    walk.scan_recursive(path, 0)?;
+    walk.reporter.report();
+    Ok(walk.result)
+}
+
+// Work-stealing walk: every directory becomes a rayon task that idle threads can steal
+fn scan_parallel(
+    path: &Path,
+    options: &ScanOptions,
+    cancel: &CancelToken,
+    on_progress: &mut dyn FnMut(&ScanProgress),
+) -> Result<ScanResult, String> {
+    struct Shared<'a> {
+        options: &'a ScanOptions,
+        cancel: &'a CancelToken,
+        entries: AtomicUsize,
+        files_found: AtomicUsize,
+        dirs_found: AtomicUsize,
+        bytes_counted: AtomicU64,
+        current_dir: Mutex<String>,
+        result: Mutex<ScanResult>,
+    }
+
+    impl Shared<'_> {
+        fn progress(&self, started: Instant) -> ScanProgress {
+            ScanProgress {
+                files_found: self.files_found.load(Ordering::Relaxed),
+                dirs_found: self.dirs_found.load(Ordering::Relaxed),
+                bytes_counted: self.bytes_counted.load(Ordering::Relaxed),
+                current_dir: self.current_dir.lock().map(|d| d.clone()).unwrap_or_default(),
+                elapsed: started.elapsed(),
+            }
+        }
+    }
+
+    fn walk_dir<'s>(scope: &rayon::Scope<'s>, shared: &'s Shared<'_>, dir: PathBuf, depth: usize) {
+        if shared.cancel.is_cancelled() {
+            return;
+        }
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        if let Ok(mut current) = shared.current_dir.try_lock() {
+            *current = dir.to_string_lossy().to_string();
+        }
+
+        let limits = shared.options.limits;
+        let mut found = Vec::new();
+        let mut skipped = Vec::new();
+        let mut hit_depth_limit = false;
+        let mut hit_entry_limit = false;
+
+        for entry in entries.flatten() {
+            if shared.cancel.is_cancelled() {
+                return;
+            }
+            let path = entry.path();
+            if shared.options.exclude.matches(&path) {
+                continue;
+            }
+            // Does not follow symlinks: a link is recorded as a plain entry and never descended
+            let metadata = match entry.metadata() {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if !metadata.is_dir() && !shared.options.filter.matches(&path) {
+                continue;
+            }
+            let info = file_info(&path, &metadata);
+            let record = shared.options.entry_filter.matches_during_scan(&info);
+            if !record && !info.is_dir {
+                continue;
+            }
+            // Mirrors the entry limit rule in scan_sequential
+            let over_limit = match limits.max_entries {
+                Some(max) if record => shared.entries.fetch_add(1, Ordering::Relaxed) >= max,
+                Some(max) => shared.entries.load(Ordering::Relaxed) >= max,
+                None => false,
+            };
+            if over_limit {
+                hit_entry_limit = true;
+                if found.is_empty() {
+                    skipped.push(dir.to_string_lossy().to_string());
+                }
+                break;
+            }
+
+            if metadata.is_dir() {
+                shared.dirs_found.fetch_add(1, Ordering::Relaxed);
+                if limits.max_depth.is_some_and(|max| depth + 1 > max) {
+                    hit_depth_limit = true;
+                    skipped.push(info.path.clone());
+                } else {
+                    scope.spawn(move |scope| walk_dir(scope, shared, path, depth + 1));
+                }
+            } else {
+                shared.files_found.fetch_add(1, Ordering::Relaxed);
+                shared.bytes_counted.fetch_add(info.size, Ordering::Relaxed);
+            }
+            if record {
+                found.push(info);
+            }
+        }
+
+        // One lock per directory keeps contention low
+        if let Ok(mut result) = shared.result.lock() {
+            result.files.append(&mut found);
+            result.skipped_dirs.append(&mut skipped);
+            result.hit_depth_limit |= hit_depth_limit;
+            result.hit_entry_limit |= hit_entry_limit;
+        }
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()
+        .map_err(|e| format!("Could not start scan threads: {}", e))?;
+
+    let shared = Shared {
+        options,
+        cancel,
+        entries: AtomicUsize::new(0),
+        files_found: AtomicUsize::new(0),
+        dirs_found: AtomicUsize::new(0),
+        bytes_counted: AtomicU64::new(0),
+        current_dir: Mutex::new(String::new()),
+        result: Mutex::new(ScanResult {
+            root: path.to_string_lossy().to_string(),
+            ..Default::default()
+        }),
+    };
+
+    let started = Instant::now();
+    let (done_sender, done_receiver) = std::sync::mpsc::channel();
+    let walked = std::thread::scope(|threads| {
+        let shared = &shared;
+        let walker = threads.spawn(move || {
+            pool.scope(|scope| walk_dir(scope, shared, path.to_path_buf(), 0));
+            let _ = done_sender.send(());
+        });
+
+        // Report from this thread while the pool walks, so the callback never has to be Send
+        loop {
+            let finished = !matches!(
+                done_receiver.recv_timeout(PROGRESS_INTERVAL),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+            );
+            on_progress(&shared.progress(started));
+            if finished {
+                break;
+            }
+        }
+        // Joined here so a panicking walker ends the scan with an error instead of taking
+        // the caller down with it when the scope ends
+        walker.join()
+    });
+
+    if walked.is_err() {
+        return Err("Scan thread panicked".into());
+    }
+    if cancel.is_cancelled() {
+        return Err("Scan cancelled".into());
+    }
+    // Only a panic while holding the lock could poison it, and that was reported above
+    Ok(shared.result.into_inner().unwrap_or_else(|e| e.into_inner()))
+}
+
+// Roll every file's size up into all of its ancestor directories (du-style totals)
+pub fn aggregate_directory_sizes(files: &mut [FileInfo]) {
+    let dir_index: HashMap<&str, usize> = files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.is_dir)
+        .map(|(i, f)| (f.path.as_str(), i))
+        .collect();
+
+    let mut totals = vec![(0u64, 0u64); files.len()];
+    for file in files.iter().filter(|f| !f.is_dir) {
+        // Directories dropped by a filter leave gaps in the chain, so look past every miss
+        for ancestor in Path::new(&file.path).ancestors().skip(1) {
+            if let Some(&i) = ancestor.to_str().and_then(|a| dir_index.get(a)) {
+                totals[i].0 += file.size;
+                totals[i].1 += 1;
+            }
+        }
+    }
+
+    for (file, (size, count)) in files.iter_mut().zip(totals) {
+        if file.is_dir {
+            file.size = size;
+            file.file_count = count;
+        }
+    }
+}
+
+// Take removed entries out of their ancestors' totals, or put restored ones back in.
+// Works from the recorded totals rather than recounting files, so results that a
+// folders-only filter left without files keep their folder sizes.
+pub fn adjust_ancestor_totals(files: &mut [FileInfo], changed: &[FileInfo], removed: bool) {
+    let changed_paths: HashSet<&str> = changed.iter().map(|f| f.path.as_str()).collect();
+    let dir_index: HashMap<String, usize> = files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.is_dir)
+        .map(|(i, f)| (f.path.clone(), i))
+        .collect();
+
+    for entry in changed {
+        let path = Path::new(&entry.path);
+        // Entries below another changed entry are already part of its total
+        if path.ancestors().skip(1).any(|a| changed_paths.contains(a.to_string_lossy().as_ref())) {
+            continue;
+        }
+        let count = if entry.is_dir { entry.file_count } else { 1 };
+        for ancestor in path.ancestors().skip(1) {
+            if let Some(&i) = ancestor.to_str().and_then(|a| dir_index.get(a)) {
+                let dir = &mut files[i];
+                if removed {
+                    dir.size = dir.size.saturating_sub(entry.size);
+                    dir.file_count = dir.file_count.saturating_sub(count);
+                } else {
+                    dir.size += entry.size;
+                    dir.file_count += count;
+                }
+            }
+        }
+    }
+}
+
+// --- CONTENT DETECTION ---
+
+// Bytes read from the start of each file; tar keeps its magic at offset 257
+const CONTENT_SNIFF_LEN: u64 = 512;
+// Files handed to the worker threads between two progress updates
+const CONTENT_SNIFF_BATCH: usize = 512;
+
+// Only regular files are read: opening a FIFO would block, and links are never followed
+fn sniff_content_type(path: &Path) -> Option<ContentType> {
+    use std::io::Read;
+
+    if !fs::symlink_metadata(path).ok()?.file_type().is_file() {
+        return None;
+    }
+    let mut open = fs::OpenOptions::new();
+    open.read(true);
+    // Still never wait on a FIFO put in the file's place after the check
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut open, libc::O_NONBLOCK | libc::O_NOFOLLOW);
+    let file = open.open(path).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+
+    let mut header = Vec::with_capacity(CONTENT_SNIFF_LEN as usize);
+    file.take(CONTENT_SNIFF_LEN).read_to_end(&mut header).ok()?;
+    ContentType::detect(&header)
+}
+
+// Optional pass after the walk: read the start of every file to learn its real type
+fn detect_content_types(
+    files: &mut [FileInfo],
+    threads: usize,
+    cancel: &CancelToken,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<(), String> {
+    use rayon::prelude::*;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| format!("Could not start scan threads: {}", e))?;
+    let total = files.iter().filter(|f| !f.is_dir).count();
+    let mut done = 0;
+    for batch in files.chunks_mut(CONTENT_SNIFF_BATCH) {
+        if cancel.is_cancelled() {
+            return Err("Scan cancelled".into());
+        }
+        pool.install(|| {
+            batch
+                .par_iter_mut()
+                .filter(|f| !f.is_dir)
+                .for_each(|f| f.content_type = sniff_content_type(Path::new(&f.path)));
+        });
+        done += batch.iter().filter(|f| !f.is_dir).count();
+        on_progress(done, total);
+    }
+    Ok(())
+}
+
+// --- DUPLICATE FINDER ---
+
+// Files with identical content; the first entry is treated as the one to keep
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub files: Vec<FileInfo>,
+}
+
+impl DuplicateGroup {
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.files.len() as u64).saturating_sub(1)
+    }
+}
+
+// Bytes read from the start of each candidate for the cheap second pass
+const PARTIAL_HASH_LEN: usize = 16 * 1024;
+// Full hashes read this much between two looks at the cancel flag
+const FULL_HASH_BLOCK_LEN: usize = 1024 * 1024;
+
+fn hash_file(path: &str, limit: Option<usize>, cancel: &CancelToken) -> std::io::Result<blake3::Hash> {
+    use std::io::Read;
+
+    let mut file = fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    match limit {
+        Some(limit) => {
+            let mut buffer = Vec::with_capacity(limit);
+            file.take(limit as u64).read_to_end(&mut buffer)?;
+            hasher.update(&buffer);
+        }
+        None => {
+            let mut buffer = vec![0; FULL_HASH_BLOCK_LEN];
+            loop {
+                if cancel.is_cancelled() {
+                    return Err(std::io::Error::other("cancelled"));
+                }
+                match file.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(read) => {
+                        hasher.update(&buffer[..read]);
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+    Ok(hasher.finalize())
+}
+
+// Narrow candidates by size, then by a hash of the first block, then by a full content hash
+pub fn find_duplicates(
+    files: &[FileInfo],
+    cancel: &CancelToken,
+    mut on_progress: impl FnMut(&str),
+) -> Result<Vec<DuplicateGroup>, String> {
+    let mut by_size: HashMap<u64, Vec<&FileInfo>> = HashMap::new();
+    for file in files.iter().filter(|f| !f.is_dir && f.size > 0) {
+        by_size.entry(file.size).or_default().push(file);
+    }
+    let mut candidates: Vec<Vec<&FileInfo>> = Vec::new();
+    for group in by_size.into_values().filter(|group| group.len() > 1) {
+        if cancel.is_cancelled() {
+            return Err("Duplicate search cancelled".into());
+        }
+        let group = unique_inodes(group);
+        if group.len() > 1 {
+            candidates.push(group);
+        }
+    }
+
+    let mut hashed = 0;
+    let mut last_report = Instant::now();
+
+    for pass in [Some(PARTIAL_HASH_LEN), None] {
+        let mut next = Vec::new();
+        for group in candidates {
+            // Files no bigger than the partial block were already compared in full
+            if pass.is_none() && group[0].size <= PARTIAL_HASH_LEN as u64 {
+                next.push(group);
+                continue;
+            }
+            let mut by_hash: HashMap<blake3::Hash, Vec<&FileInfo>> = HashMap::new();
+            for file in group {
+                let hash = hash_file(&file.path, pass, cancel);
+                if cancel.is_cancelled() {
+                    return Err("Duplicate search cancelled".into());
+                }
+                // Unreadable files simply drop out of the comparison
+                if let Ok(hash) = hash {
+                    by_hash.entry(hash).or_default().push(file);
+                }
+                hashed += 1;
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    let stage = if pass.is_some() { "Comparing first blocks" } else { "Comparing full contents" };
+                    on_progress(&format!("{}... {} files hashed", stage, hashed));
+                }
+            }
+            next.extend(by_hash.into_values().filter(|g| g.len() > 1));
+        }
+        candidates = next;
+    }
+
+    let mut groups: Vec<DuplicateGroup> = candidates
+        .into_iter()
+        .map(|mut group| {
+            group.sort_by(|a, b| a.path.cmp(&b.path));
+            DuplicateGroup {
+                size: group[0].size,
+                files: group.into_iter().cloned().collect(),
+            }
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted()));
+    Ok(groups)
+}
+
+// Hard links share their data, so only one path per inode counts as a candidate.
+// Symlinks are recorded with the size of the link itself and never count.
+#[cfg(unix)]
+fn unique_inodes(group: Vec<&FileInfo>) -> Vec<&FileInfo> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut seen = HashSet::new();
+    group
+        .into_iter()
+        .filter(|file| match fs::symlink_metadata(&file.path) {
+            Ok(meta) if !meta.file_type().is_symlink() => seen.insert((meta.dev(), meta.ino())),
+            _ => false,
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn unique_inodes(group: Vec<&FileInfo>) -> Vec<&FileInfo> {
+    group
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::EntryKind;
+
+    // Fresh directory under the system temp dir, unique per test and process
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("disk_maid_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn scan(dir: &Path) -> Vec<FileInfo> {
+        let options = ScanOptions {
+            threads: 1,
+            ..Default::default()
+        };
+        scan_directory(dir.to_path_buf(), &options, &CancelToken::default(), |_| {})
+            .unwrap()
+            .files
+    }
+
+    fn matches(spec: &str, path: &str) -> bool {
+        FilterSet::parse(spec).unwrap().matches(Path::new(path))
+    }
+
+    #[test]
+    fn split_top_level_ignores_separators_inside_groups() {
+        assert_eq!(
+            split_top_level("a,{b,c};[,;]x, y", &[',', ';']),
+            vec!["a", "{b,c}", "[,;]x", " y"]
+        );
+        assert_eq!(split_top_level("", &[',']), vec![""]);
+    }
+
+    #[test]
+    fn expand_braces_handles_nested_groups() {
+        assert_eq!(expand_braces("*.{jpg,png}").unwrap(), vec!["*.jpg", "*.png"]);
+        assert_eq!(expand_braces("a{b,{c,d}}e").unwrap(), vec!["abe", "ace", "ade"]);
+        assert_eq!(expand_braces("{a,b}{1,2}").unwrap(), vec!["a1", "a2", "b1", "b2"]);
+        assert_eq!(expand_braces("plain").unwrap(), vec!["plain"]);
+    }
+
+    #[test]
+    fn expand_braces_rejects_unbalanced_braces() {
+        assert!(expand_braces("*.{jpg").is_err());
+        assert!(expand_braces("*.jpg}").is_err());
+        assert!(FilterSet::parse("{a,b").is_err());
+    }
+
+    #[test]
+    fn star_dot_star_matches_files_without_extension() {
+        assert!(matches("*.*", "/data/Makefile"));
+        assert!(matches("*.*", "/data/photo.jpg"));
+    }
+
+    #[test]
+    fn negation_excludes_from_includes() {
+        assert!(matches("*.rs; !main.rs", "/src/lib.rs"));
+        assert!(!matches("*.rs; !main.rs", "/src/main.rs"));
+        assert!(!matches("*.rs; !main.rs", "/src/notes.txt"));
+        // Only exclusions: everything else is kept
+        assert!(matches("!*.tmp", "/data/report.pdf"));
+        assert!(!matches("!*.tmp", "/data/build.tmp"));
+    }
+
+    #[test]
+    fn patterns_are_case_insensitive_and_match_the_name() {
+        assert!(matches("*.JPG", "/photos/holiday.jpg"));
+        assert!(matches("report_*.pdf", "/docs/2024/report_may.pdf"));
+        assert!(!matches("report_*.pdf", "/docs/report_may.pdf.bak"));
+    }
+
+    #[test]
+    fn separators_inside_classes_and_braces_stay_in_one_pattern() {
+        assert!(matches("file[,;]x", "/data/file,x"));
+        assert!(matches("file[,;]x", "/data/file;x"));
+        assert!(matches("*.{jpg,png}; *.gif", "/data/a.png"));
+        assert!(matches("*.{jpg,png}; *.gif", "/data/a.gif"));
+        assert!(!matches("*.{jpg,png}; *.gif", "/data/a.bmp"));
+    }
+
+    #[test]
+    fn patterns_with_a_separator_match_the_full_path() {
+        assert!(matches("/tmp/*/keep.txt", "/tmp/a/keep.txt"));
+        assert!(!matches("/tmp/*/keep.txt", "/tmp/a/b/keep.txt"));
+        assert!(!matches("/tmp/*/keep.txt", "/other/a/keep.txt"));
+    }
+
+    #[test]
+    fn directory_totals_skip_over_missing_ancestors() {
+        let entry = |path: &str, is_dir: bool, size: u64| FileInfo {
+            path: path.to_string(),
+            size,
+            is_dir,
+            modified: 0,
+            file_count: 0,
+            content_type: None,
+        };
+        // "/root/a/b" was dropped by a date filter during the walk
+        let mut files = vec![
+            entry("/root/a", true, 0),
+            entry("/root/a/b/big.bin", false, 10_240),
+            entry("/root/a/small.txt", false, 10),
+        ];
+        aggregate_directory_sizes(&mut files);
+        assert_eq!(files[0].size, 10_250);
+        assert_eq!(files[0].file_count, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_recorded_but_never_walked() {
+        let dir = fixture("symlinks");
+        fs::create_dir(dir.join("a")).unwrap();
+        fs::write(dir.join("a/file"), vec![0u8; 1000]).unwrap();
+        std::os::unix::fs::symlink("..", dir.join("a/up")).unwrap();
+        std::os::unix::fs::symlink(".", dir.join("a/self")).unwrap();
+
+        for threads in [1, 4] {
+            let options = ScanOptions {
+                threads,
+                ..Default::default()
+            };
+            let result = scan_directory(dir.clone(), &options, &CancelToken::default(), |_| {}).unwrap();
+            assert_eq!(result.files.len(), 4);
+            let up = result.files.iter().find(|f| f.path.ends_with("/a/up")).unwrap();
+            assert!(!up.is_dir);
+            let a = result.files.iter().find(|f| f.is_dir).unwrap();
+            // The links count with their own tiny size, not their targets'
+            assert!(a.size < 1100);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn content_detection_skips_fifos_and_links() {
+        let dir = fixture("fifo");
+        fs::write(dir.join("image.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        let fifo = std::ffi::CString::new(dir.join("pipe").to_string_lossy().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+        std::os::unix::fs::symlink("image.png", dir.join("link")).unwrap();
+
+        // Opening the FIFO for reading would wait forever for a writer
+        let options = ScanOptions {
+            threads: 1,
+            detect_content: true,
+            ..Default::default()
+        };
+        let result = scan_directory(dir.clone(), &options, &CancelToken::default(), |_| {}).unwrap();
+        let content = |name: &str| result.files.iter().find(|f| f.path.ends_with(name)).unwrap().content_type;
+        assert!(content("image.png").is_some());
+        assert_eq!(content("pipe"), None);
+        assert_eq!(content("link"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn both_walkers_skip_the_same_folders_at_the_entry_limit() {
+        let dir = fixture("entry_limit");
+        fs::create_dir_all(dir.join("a/b/c")).unwrap();
+        for name in ["x", "y", "z"] {
+            fs::write(dir.join("a/b/c").join(name), b"data").unwrap();
+        }
+
+        // Only a folder the limit left completely unread counts as skipped
+        let expected = [
+            (1, vec!["a"]),
+            (2, vec!["a/b"]),
+            (3, vec!["a/b/c"]),
+            (4, vec![]),
+            (5, vec![]),
+        ];
+        for (max_entries, skipped) in expected {
+            for threads in [1, 4] {
+                let options = ScanOptions {
+                    threads,
+                    limits: ScanLimits {
+                        max_entries: Some(max_entries),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                let result = scan_directory(dir.clone(), &options, &CancelToken::default(), |_| {}).unwrap();
+                let expected: Vec<String> = skipped.iter().map(|p| dir.join(p).to_string_lossy().to_string()).collect();
+                assert_eq!(result.skipped_dirs, expected, "max_entries = {}, threads = {}", max_entries, threads);
+                assert_eq!(result.files.len(), max_entries, "max_entries = {}, threads = {}", max_entries, threads);
+                assert!(result.hit_entry_limit);
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn duplicates_need_identical_full_contents() {
+        let dir = fixture("duplicates");
+        let content = vec![7u8; 3 * PARTIAL_HASH_LEN];
+        let mut different_tail = content.clone();
+        *different_tail.last_mut().unwrap() = 8;
+        fs::write(dir.join("a.bin"), &content).unwrap();
+        fs::write(dir.join("b.bin"), &content).unwrap();
+        fs::write(dir.join("c.bin"), &different_tail).unwrap();
+        fs::write(dir.join("short.bin"), &content[..100]).unwrap();
+        fs::write(dir.join("empty1"), b"").unwrap();
+        fs::write(dir.join("empty2"), b"").unwrap();
+
+        let groups = find_duplicates(&scan(&dir), &CancelToken::default(), |_| {}).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(groups.len(), 1);
+        let mut names: Vec<String> = groups[0]
+            .files
+            .iter()
+            .map(|f| Path::new(&f.path).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.bin", "b.bin"]);
+        assert_eq!(groups[0].wasted(), content.len() as u64);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_not_duplicates_of_each_other() {
+        let dir = fixture("hard_links");
+        fs::write(dir.join("a.bin"), b"same bytes").unwrap();
+        fs::hard_link(dir.join("a.bin"), dir.join("a-link.bin")).unwrap();
+
+        let groups = find_duplicates(&scan(&dir), &CancelToken::default(), |_| {}).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn cancelled_duplicate_search_returns_an_error() {
+        let dir = fixture("cancelled_duplicates");
+        fs::write(dir.join("a.bin"), b"same bytes").unwrap();
+        fs::write(dir.join("b.bin"), b"same bytes").unwrap();
+        let files = scan(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        assert!(find_duplicates(&files, &cancel, |_| {}).is_err());
+    }
+
+    #[test]
+    fn folders_only_scans_report_folder_totals() {
+        let dir = fixture("folders_only");
+        fs::create_dir_all(dir.join("heavy/inner")).unwrap();
+        fs::create_dir(dir.join("light")).unwrap();
+        fs::write(dir.join("heavy/inner/big.bin"), vec![0u8; 5000]).unwrap();
+        fs::write(dir.join("heavy/top.bin"), vec![0u8; 1000]).unwrap();
+        fs::write(dir.join("light/small.bin"), vec![0u8; 10]).unwrap();
+
+        for threads in [1, 4] {
+            let mut options = ScanOptions {
+                threads,
+                ..Default::default()
+            };
+            options.entry_filter.kind = EntryKind::DirsOnly;
+            options.entry_filter.min_size = Some(100);
+            let result = scan_directory(dir.clone(), &options, &CancelToken::default(), |_| {}).unwrap();
+
+            let mut sizes: Vec<(String, u64, u64)> = result
+                .files
+                .iter()
+                .map(|f| (f.path[dir.to_string_lossy().len()..].to_string(), f.size, f.file_count))
+                .collect();
+            sizes.sort();
+            let expected = [("/heavy", 6000, 2), ("/heavy/inner", 5000, 1)];
+            let expected: Vec<(String, u64, u64)> = expected.iter().map(|&(p, s, c)| (p.to_string(), s, c)).collect();
+            assert_eq!(sizes, expected, "threads = {}", threads);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ancestor_totals_follow_removed_and_restored_entries() {
+        let entry = |path: &str, is_dir: bool, size: u64, file_count: u64| FileInfo {
+            path: path.to_string(),
+            size,
+            is_dir,
+            modified: 0,
+            file_count,
+            content_type: None,
+        };
+        let mut files = vec![entry("/r/a", true, 300, 3), entry("/r/a/b", true, 200, 2)];
+        let removed = vec![entry("/r/a/b", true, 200, 2), entry("/r/a/b/x", false, 150, 0)];
+        adjust_ancestor_totals(&mut files, &removed, true);
+        assert_eq!((files[0].size, files[0].file_count), (100, 1));
+
+        files.truncate(1);
+        adjust_ancestor_totals(&mut files, &removed, false);
+        assert_eq!((files[0].size, files[0].file_count), (300, 3));
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let set = FilterSet::parse(" ; , ").unwrap();
+        assert!(set.matches(Path::new("/anything")));
+        assert_eq!(set.describe(), "Matches every file");
+    }
+}
@@ -0,0 +1,575 @@
+// Saved scans for reopening later and comparing over time
+
+use crate::model::{FileInfo, ScanResult, ScanScope};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+// --- SNAPSHOTS ---
+
+// Bumped whenever the snapshot layout changes; files of other versions are refused, not misread
+const SNAPSHOT_VERSION: u32 = 1;
+// Uncompressed first line of every snapshot file, followed by the version number
+const SNAPSHOT_MAGIC: &str = "disk-maid-snapshot";
+// Snapshots kept on disk; the oldest are removed after each save
+pub const MAX_SNAPSHOTS: usize = 20;
+
+// What the Recent Scans list shows, kept in a small index so listing never decompresses a scan
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotSummary {
+    // File name inside the snapshot directory
+    pub file: String,
+    pub root: String,
+    // Unix timestamp (seconds) of the end of the scan
+    pub created: u64,
+    pub files: usize,
+    pub dirs: usize,
+    pub bytes: u64,
+}
+
+// A completed scan as stored on disk: a version line, then gzip-compressed JSON
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    pub summary: SnapshotSummary,
+    pub files: Vec<FileInfo>,
+    pub skipped_dirs: Vec<String>,
+    pub hit_depth_limit: bool,
+    pub hit_entry_limit: bool,
+    // Missing from snapshots saved before it was recorded
+    #[serde(default)]
+    pub scope: Option<ScanScope>,
+}
+
+impl Snapshot {
+    pub fn new(result: &ScanResult) -> Self {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        let summary = SnapshotSummary {
+            file: format!("scan-{}.json.gz", now.as_millis()),
+            root: result.root.clone(),
+            created: now.as_secs(),
+            files: result.files.iter().filter(|f| !f.is_dir).count(),
+            dirs: result.files.iter().filter(|f| f.is_dir).count(),
+            bytes: result.files.iter().filter(|f| !f.is_dir).map(|f| f.size).sum(),
+        };
+        Self {
+            summary,
+            files: result.files.clone(),
+            skipped_dirs: result.skipped_dirs.clone(),
+            hit_depth_limit: result.hit_depth_limit,
+            hit_entry_limit: result.hit_entry_limit,
+            scope: Some(result.scope.clone()),
+        }
+    }
+}
+
+fn get_snapshot_dir() -> Result<PathBuf, anyhow::Error> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?;
+    let snapshot_dir = data_dir.join("disk-maid-rs").join("snapshots");
+    if !snapshot_dir.exists() {
+        fs::create_dir_all(&snapshot_dir)?;
+    }
+    Ok(snapshot_dir)
+}
+
+// Serializes the read-modify-write of index.json between the threads that save and remove snapshots
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+// Newest first; entries whose snapshot file has gone missing are dropped
+pub fn load_snapshot_index() -> Result<Vec<SnapshotSummary>, anyhow::Error> {
+    read_index(&get_snapshot_dir()?)
+}
+
+// An index that cannot be read is rebuilt from the snapshot files, so the scans it listed
+// are neither forgotten nor left on disk unpruned once the next save rewrites it
+fn read_index(dir: &Path) -> Result<Vec<SnapshotSummary>, anyhow::Error> {
+    let path = dir.join("index.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let parsed = fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(serde_json::from_str::<Vec<SnapshotSummary>>(&content)?));
+    let Ok(mut index) = parsed else {
+        return rebuild_index(dir);
+    };
+    index.retain(|s| dir.join(&s.file).exists());
+    Ok(index)
+}
+
+// Newest first, from the summary stored in every readable snapshot file of `dir`
+fn rebuild_index(dir: &Path) -> Result<Vec<SnapshotSummary>, anyhow::Error> {
+    let mut index = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if !name.starts_with("scan-") || !name.ends_with(".json.gz") {
+            continue;
+        }
+        // Placeholders of saves still in progress and damaged files are skipped
+        if let Ok(snapshot) = read_snapshot(&dir.join(&name)) {
+            index.push(SnapshotSummary { file: name, ..snapshot.summary });
+        }
+    }
+    index.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.file.cmp(&a.file)));
+    Ok(index)
+}
+
+fn write_index(dir: &Path, index: &[SnapshotSummary]) -> Result<(), anyhow::Error> {
+    let json = serde_json::to_string_pretty(index)?;
+    write_atomically(&dir.join("index.json"), |file| Ok(file.write_all(json.as_bytes())?))
+}
+
+// Writes into a temporary file next to `path` and renames it over, so a crash never leaves a truncated file
+fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<fs::File>) -> Result<(), anyhow::Error>,
+) -> Result<(), anyhow::Error> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp = path.with_file_name(temp_name);
+
+    let written = (|| -> Result<(), anyhow::Error> {
+        let mut file = BufWriter::new(fs::File::create(&temp)?);
+        write(&mut file)?;
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+// Writes the snapshot, records it in the index and prunes the oldest beyond MAX_SNAPSHOTS.
+// The file name may get a counter if it is already taken. Returns the updated index.
+pub fn save_snapshot(snapshot: &mut Snapshot) -> Result<Vec<SnapshotSummary>, String> {
+    get_snapshot_dir()
+        .and_then(|dir| save_snapshot_in(&dir, snapshot))
+        .map_err(|e| format!("Could not save scan snapshot: {}", e))
+}
+
+fn save_snapshot_in(dir: &Path, snapshot: &mut Snapshot) -> Result<Vec<SnapshotSummary>, anyhow::Error> {
+    snapshot.summary.file = reserve_file_name(dir, &snapshot.summary.file)?;
+    let path = dir.join(&snapshot.summary.file);
+    let written = write_atomically(&path, |file| {
+        writeln!(file, "{} {}", SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        serde_json::to_writer(&mut encoder, &*snapshot)?;
+        encoder.finish()?;
+        Ok(())
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&path);
+    }
+    written?;
+
+    let _index_guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index = read_index(dir)?;
+    // A rebuilt index already found the file just written
+    index.retain(|s| s.file != snapshot.summary.file);
+    index.insert(0, snapshot.summary.clone());
+    for old in index.split_off(MAX_SNAPSHOTS.min(index.len())) {
+        let _ = fs::remove_file(dir.join(&old.file));
+    }
+    write_index(dir, &index)?;
+    Ok(index)
+}
+
+// Claims `preferred`, or "<stem>-<n>.json.gz" when another save already holds it (two saves
+// in the same millisecond), by creating an empty placeholder that the real file replaces
+fn reserve_file_name(dir: &Path, preferred: &str) -> Result<String, anyhow::Error> {
+    let stem = preferred.strip_suffix(".json.gz").unwrap_or(preferred);
+    let mut counter = 0;
+    loop {
+        let name = match counter {
+            0 => preferred.to_string(),
+            n => format!("{}-{}.json.gz", stem, n),
+        };
+        match fs::OpenOptions::new().write(true).create_new(true).open(dir.join(&name)) {
+            Ok(_) => return Ok(name),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => counter += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+pub fn load_snapshot(summary: &SnapshotSummary) -> Result<Snapshot, String> {
+    get_snapshot_dir()
+        .and_then(|dir| read_snapshot(&dir.join(&summary.file)))
+        .map_err(|e| format!("Could not open snapshot of {}: {}", summary.root, e))
+}
+
+fn read_snapshot(path: &Path) -> Result<Snapshot, anyhow::Error> {
+    use std::io::BufRead;
+
+    let mut file = std::io::BufReader::new(fs::File::open(path)?);
+    let mut header = String::new();
+    file.read_line(&mut header)?;
+    let version = header
+        .trim_end()
+        .strip_prefix(SNAPSHOT_MAGIC)
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .ok_or_else(|| anyhow::anyhow!("not a Disk Maid snapshot"))?;
+    if version != SNAPSHOT_VERSION {
+        anyhow::bail!("unsupported snapshot version {} (expected {})", version, SNAPSHOT_VERSION);
+    }
+    Ok(serde_json::from_reader(flate2::read::GzDecoder::new(file))?)
+}
+
+// Removes the snapshot file and its index entry; returns the updated index
+pub fn remove_snapshot(summary: &SnapshotSummary) -> Result<Vec<SnapshotSummary>, String> {
+    get_snapshot_dir()
+        .and_then(|dir| remove_snapshot_in(&dir, summary))
+        .map_err(|e| format!("Could not remove snapshot: {}", e))
+}
+
+fn remove_snapshot_in(dir: &Path, summary: &SnapshotSummary) -> Result<Vec<SnapshotSummary>, anyhow::Error> {
+    let _index_guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = fs::remove_file(dir.join(&summary.file));
+    let index: Vec<SnapshotSummary> = read_index(dir)?.into_iter().filter(|s| s.file != summary.file).collect();
+    write_index(dir, &index)?;
+    Ok(index)
+}
+
+// --- SNAPSHOT COMPARISON ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ChangeKind::Added => "Added",
+                ChangeKind::Removed => "Removed",
+                ChangeKind::Grown => "Grown",
+                ChangeKind::Shrunk => "Shrunk",
+            }
+        )
+    }
+}
+
+// How one file or folder changed between two snapshots; unchanged paths are not listed
+#[derive(Debug, Clone)]
+pub struct PathDelta {
+    pub path: String,
+    pub is_dir: bool,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub kind: ChangeKind,
+}
+
+impl PathDelta {
+    pub fn delta(&self) -> i64 {
+        self.new_size as i64 - self.old_size as i64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanComparison {
+    pub older: SnapshotSummary,
+    pub newer: SnapshotSummary,
+    // Largest growth first, largest shrink last
+    pub deltas: Vec<PathDelta>,
+    // Why some of the deltas may not be real changes on disk; empty when both scans are comparable
+    pub warnings: Vec<String>,
+}
+
+impl ScanComparison {
+    // Change in total file bytes under the root
+    pub fn net_change(&self) -> i64 {
+        self.newer.bytes as i64 - self.older.bytes as i64
+    }
+
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.deltas.iter().filter(|d| d.kind == kind).count()
+    }
+}
+
+// Diffs two snapshots of the same root path by path; their order does not matter
+pub fn compare_snapshots(a: &Snapshot, b: &Snapshot) -> Result<ScanComparison, String> {
+    if a.summary.root != b.summary.root {
+        return Err(format!(
+            "Snapshots are of different folders ({} and {})",
+            a.summary.root, b.summary.root
+        ));
+    }
+    let (older, newer) = if a.summary.created <= b.summary.created { (a, b) } else { (b, a) };
+
+    let old_sizes: HashMap<&str, &FileInfo> = older.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let mut deltas = Vec::new();
+    let mut seen = HashSet::new();
+    for file in &newer.files {
+        seen.insert(file.path.as_str());
+        let (old_size, kind) = match old_sizes.get(file.path.as_str()) {
+            None => (0, ChangeKind::Added),
+            Some(old) if file.size > old.size => (old.size, ChangeKind::Grown),
+            Some(old) if file.size < old.size => (old.size, ChangeKind::Shrunk),
+            Some(_) => continue,
+        };
+        deltas.push(PathDelta {
+            path: file.path.clone(),
+            is_dir: file.is_dir,
+            old_size,
+            new_size: file.size,
+            kind,
+        });
+    }
+    for file in older.files.iter().filter(|f| !seen.contains(f.path.as_str())) {
+        deltas.push(PathDelta {
+            path: file.path.clone(),
+            is_dir: file.is_dir,
+            old_size: file.size,
+            new_size: 0,
+            kind: ChangeKind::Removed,
+        });
+    }
+    deltas.sort_by(|x, y| y.delta().cmp(&x.delta()).then_with(|| x.path.cmp(&y.path)));
+
+    Ok(ScanComparison {
+        older: older.summary.clone(),
+        newer: newer.summary.clone(),
+        deltas,
+        warnings: comparison_warnings(older, newer),
+    })
+}
+
+// An incomplete scan, or one that recorded a different set of entries, makes everything
+// the other one has look added or removed
+fn comparison_warnings(older: &Snapshot, newer: &Snapshot) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (name, snapshot) in [("older", older), ("newer", newer)] {
+        if snapshot.hit_entry_limit {
+            warnings.push(format!(
+                "The {} scan stopped at the entry limit, so it is missing entries",
+                name
+            ));
+        } else if !snapshot.skipped_dirs.is_empty() {
+            warnings.push(format!(
+                "The {} scan skipped {} folders at the depth limit, so their contents are missing",
+                name,
+                snapshot.skipped_dirs.len()
+            ));
+        }
+    }
+    match (&older.scope, &newer.scope) {
+        (Some(a), Some(b)) if a == b => {}
+        (Some(_), Some(_)) => warnings.push(
+            "The scans used different filters or exclusions, so entries only one of them recorded show up as added or removed"
+                .into(),
+        ),
+        _ => warnings.push("A scan was saved without its filter settings, so they could not be checked".into()),
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FileCategory, ResultFilter, TypeFilter};
+
+    fn snapshot(root: &str, created: u64, files: &[(&str, u64)]) -> Snapshot {
+        let result = ScanResult {
+            root: root.to_string(),
+            files: files
+                .iter()
+                .map(|&(path, size)| FileInfo {
+                    path: path.to_string(),
+                    size,
+                    is_dir: false,
+                    modified: 0,
+                    file_count: 0,
+                    content_type: None,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut snapshot = Snapshot::new(&result);
+        snapshot.summary.created = created;
+        snapshot
+    }
+
+    #[test]
+    fn concurrent_saves_and_removes_keep_every_index_change() {
+        let dir = std::env::temp_dir().join(format!("disk_maid_snapshots_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let numbered = |i: u64| {
+            let mut snapshot = snapshot("/data", i, &[("/data/file", i)]);
+            snapshot.summary.file = format!("scan-{}.json.gz", i);
+            snapshot
+        };
+        let mut old: Vec<Snapshot> = (0..4).map(numbered).collect();
+        for snapshot in &mut old {
+            save_snapshot_in(&dir, snapshot).unwrap();
+        }
+
+        std::thread::scope(|threads| {
+            for (i, snapshot) in old.iter().enumerate() {
+                let dir = &dir;
+                threads.spawn(move || remove_snapshot_in(dir, &snapshot.summary).unwrap());
+                threads.spawn(move || save_snapshot_in(dir, &mut numbered(10 + i as u64)).unwrap());
+            }
+        });
+        let mut files: Vec<String> = read_index(&dir).unwrap().into_iter().map(|s| s.file).collect();
+        files.sort();
+        let mut on_disk: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        on_disk.sort();
+        let reopened = read_snapshot(&dir.join("scan-12.json.gz")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let saved: Vec<String> = (10..14).map(|i| format!("scan-{}.json.gz", i)).collect();
+        assert_eq!(files, saved);
+        // No temporary files left behind
+        assert_eq!(on_disk, [&["index.json".to_string()][..], &saved].concat());
+        assert_eq!(reopened.files[0].size, 12);
+    }
+
+    #[test]
+    fn saves_with_the_same_file_name_are_both_kept() {
+        let dir = std::env::temp_dir().join(format!("disk_maid_snapshot_names_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // Both taken in the same millisecond
+        let mut first = snapshot("/data", 100, &[("/data/file", 1)]);
+        let mut second = snapshot("/data", 100, &[("/data/file", 2)]);
+        first.summary.file = "scan-100.json.gz".into();
+        second.summary.file = "scan-100.json.gz".into();
+        save_snapshot_in(&dir, &mut first).unwrap();
+        let index = save_snapshot_in(&dir, &mut second).unwrap();
+        let sizes: Vec<u64> = index
+            .iter()
+            .map(|s| read_snapshot(&dir.join(&s.file)).unwrap().files[0].size)
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(second.summary.file, "scan-100-1.json.gz");
+        assert_eq!(index[0].file, "scan-100-1.json.gz");
+        assert_eq!(sizes, vec![2, 1]);
+    }
+
+    #[test]
+    fn a_damaged_index_is_rebuilt_from_the_snapshot_files() {
+        let dir = std::env::temp_dir().join(format!("disk_maid_snapshot_index_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for i in 1..=2 {
+            let mut older = snapshot("/data", i, &[("/data/file", i)]);
+            older.summary.file = format!("scan-{}.json.gz", i);
+            save_snapshot_in(&dir, &mut older).unwrap();
+        }
+        fs::write(dir.join("index.json"), "{ not json").unwrap();
+
+        let mut newest = snapshot("/data", 3, &[("/data/file", 3)]);
+        newest.summary.file = "scan-3.json.gz".into();
+        let index = save_snapshot_in(&dir, &mut newest).unwrap();
+        let reread = read_index(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let files: Vec<&str> = index.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(files, ["scan-3.json.gz", "scan-2.json.gz", "scan-1.json.gz"]);
+        assert_eq!(reread, index);
+    }
+
+    #[test]
+    fn comparison_lists_every_kind_of_change_largest_growth_first() {
+        let old = [("/data/same", 10), ("/data/grown", 100), ("/data/shrunk", 500), ("/data/gone", 40)];
+        let new = [("/data/same", 10), ("/data/grown", 1_100), ("/data/shrunk", 20), ("/data/new", 30)];
+        let (old, new) = (snapshot("/data", 100, &old), snapshot("/data", 200, &new));
+
+        let comparison = compare_snapshots(&old, &new).unwrap();
+        let changes: Vec<(&str, ChangeKind, i64)> = comparison
+            .deltas
+            .iter()
+            .map(|d| (d.path.as_str(), d.kind, d.delta()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("/data/grown", ChangeKind::Grown, 1_000),
+                ("/data/new", ChangeKind::Added, 30),
+                ("/data/gone", ChangeKind::Removed, -40),
+                ("/data/shrunk", ChangeKind::Shrunk, -480),
+            ]
+        );
+        assert_eq!(comparison.net_change(), 1_160 - 650);
+        assert_eq!(comparison.count(ChangeKind::Added), 1);
+    }
+
+    #[test]
+    fn comparison_orders_snapshots_by_creation_time() {
+        let old = snapshot("/data", 100, &[("/data/file", 10)]);
+        let new = snapshot("/data", 200, &[("/data/file", 30)]);
+
+        let comparison = compare_snapshots(&new, &old).unwrap();
+        assert_eq!(comparison.older.created, 100);
+        assert_eq!(comparison.newer.created, 200);
+        assert_eq!(comparison.deltas[0].kind, ChangeKind::Grown);
+    }
+
+    #[test]
+    fn comparison_warns_about_incomplete_or_differently_filtered_scans() {
+        let old = snapshot("/data", 100, &[("/data/file", 10)]);
+        let new = snapshot("/data", 200, &[("/data/file", 30)]);
+        assert!(compare_snapshots(&old, &new).unwrap().warnings.is_empty());
+
+        let mut truncated = new.clone();
+        truncated.hit_entry_limit = true;
+        truncated.skipped_dirs = vec!["/data/big".into()];
+        let warnings = compare_snapshots(&old, &truncated).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("newer scan stopped at the entry limit"));
+
+        let mut filtered = new.clone();
+        filtered.scope = Some(ScanScope {
+            filter: vec!["*.log".into()],
+            ..Default::default()
+        });
+        let warnings = compare_snapshots(&filtered, &old).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("different filters"));
+
+        // The baked-in filter is compared by value, also after a round trip through the file format
+        let baked = ScanScope {
+            entry_filter: Some(ResultFilter {
+                min_size: Some(1024),
+                file_type: TypeFilter::Category(FileCategory::Video),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut with_filter = new.clone();
+        with_filter.scope = Some(baked.clone());
+        let mut reloaded = old.clone();
+        reloaded.scope = serde_json::from_str(&serde_json::to_string(&with_filter.scope).unwrap()).unwrap();
+        assert!(compare_snapshots(&reloaded, &with_filter).unwrap().warnings.is_empty());
+        reloaded.scope.as_mut().unwrap().entry_filter.as_mut().unwrap().min_size = Some(2048);
+        assert_eq!(compare_snapshots(&reloaded, &with_filter).unwrap().warnings.len(), 1);
+
+        let mut unknown = old.clone();
+        unknown.scope = None;
+        assert_eq!(compare_snapshots(&unknown, &new).unwrap().warnings.len(), 1);
+    }
+
+    #[test]
+    fn comparison_refuses_different_roots() {
+        let a = snapshot("/data", 100, &[]);
+        let b = snapshot("/other", 200, &[]);
+        assert!(compare_snapshots(&a, &b).is_err());
+    }
+}
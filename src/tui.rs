@@ -0,0 +1,416 @@
+// Terminal frontend for SSH sessions
+
+use disk_maid::actions::{delete_permanently, move_to_trash};
+use disk_maid::config::AppConfig;
+use disk_maid::model::{FileInfo, ScanResult, ScanTree, SortMethod};
+use disk_maid::scanner::adjust_ancestor_totals;
+use std::{collections::HashSet, path::Path};
+
+// --- TERMINAL UI ---
+
+// Width of the size bar drawn next to every entry
+const TUI_BAR_WIDTH: usize = 10;
+
+// ncdu-style browser over a finished scan, for SSH sessions
+struct TuiApp {
+    config: AppConfig,
+    files: Vec<FileInfo>,
+    tree: ScanTree,
+    // Directory being listed and its children in display order
+    cursor: usize,
+    children: Vec<usize>,
+    list: ratatui::widgets::ListState,
+    sort: SortMethod,
+    // Entry waiting for y/n
+    pending_delete: Option<usize>,
+    status: String,
+}
+
+impl TuiApp {
+    fn new(config: AppConfig, result: ScanResult) -> Self {
+        let mut app = TuiApp {
+            sort: config.default_sort,
+            config,
+            tree: ScanTree::build(&result.root, &result.files),
+            files: result.files,
+            cursor: ScanTree::ROOT,
+            children: Vec::new(),
+            list: ratatui::widgets::ListState::default(),
+            pending_delete: None,
+            status: String::new(),
+        };
+        app.refresh_children(None);
+        app
+    }
+
+    // Re-lists the current directory, keeping `keep` selected if it is still there
+    fn refresh_children(&mut self, keep: Option<usize>) {
+        self.children = self.tree.sorted_children(self.cursor, self.sort);
+        let index = keep
+            .and_then(|id| self.children.iter().position(|&c| c == id))
+            .or(self.list.selected())
+            .map(|i| i.min(self.children.len().saturating_sub(1)));
+        self.list.select(if self.children.is_empty() { None } else { index.or(Some(0)) });
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list.selected().and_then(|i| self.children.get(i).copied())
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.children.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let last = self.children.len() as isize - 1;
+        self.list.select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    fn open_selected(&mut self) {
+        if let Some(id) = self.selected().filter(|&id| self.tree.node(id).info.is_dir) {
+            self.cursor = id;
+            self.list.select(None);
+            self.refresh_children(None);
+        }
+    }
+
+    fn go_up(&mut self) {
+        if let Some(parent) = self.tree.node(self.cursor).parent {
+            let came_from = self.cursor;
+            self.cursor = parent;
+            self.refresh_children(Some(came_from));
+        }
+    }
+
+    fn set_sort(&mut self, sort: SortMethod) {
+        let keep = self.selected();
+        self.sort = sort;
+        self.refresh_children(keep);
+        self.status = format!("Sorted by {}", sort);
+    }
+
+    fn delete(&mut self, id: usize) {
+        let path = self.tree.node(id).info.path.clone();
+        let removed: HashSet<String> = if self.config.deletes_permanently() {
+            let report = delete_permanently(Path::new(&path));
+            self.status = match report.failures.first() {
+                None => format!("Deleted {}", path),
+                Some(error) => format!(
+                    "Deleted {} items, {} could not be removed: {}",
+                    report.removed.len(),
+                    report.failures.len(),
+                    error
+                ),
+            };
+            report.removed.into_iter().collect()
+        } else {
+            match move_to_trash(Path::new(&path)) {
+                Ok(_) => {
+                    self.status = format!("Moved to Trash: {}", path);
+                    self.files
+                        .iter()
+                        .filter(|f| Path::new(&f.path).starts_with(&path))
+                        .map(|f| f.path.clone())
+                        .collect()
+                }
+                Err(e) => {
+                    self.status = format!("Failed to move to Trash: {}", e);
+                    HashSet::new()
+                }
+            }
+        };
+        if removed.is_empty() {
+            return;
+        }
+
+        let dropped: Vec<FileInfo> = self.files.iter().filter(|f| removed.contains(&f.path)).cloned().collect();
+        adjust_ancestor_totals(&mut self.files, &dropped, true);
+        self.files.retain(|f| !removed.contains(&f.path));
+        let root = self.tree.node(ScanTree::ROOT).info.path.clone();
+        let cursor = self.tree.node(self.cursor).info.path.clone();
+        self.tree = ScanTree::build(&root, &self.files);
+        // Stay in the same folder, or the closest one that still exists
+        self.cursor = Path::new(&cursor)
+            .ancestors()
+            .find_map(|dir| self.tree.find(dir.to_string_lossy().as_ref()))
+            .unwrap_or(ScanTree::ROOT);
+        self.refresh_children(None);
+    }
+
+    // Returns false when the user asked to quit
+    fn handle_key(&mut self, key: ratatui::crossterm::event::KeyEvent) -> bool {
+        use ratatui::crossterm::event::KeyCode;
+
+        if let Some(id) = self.pending_delete.take() {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete(id);
+            } else {
+                self.status = "Deletion cancelled.".into();
+            }
+            return true;
+        }
+
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-20),
+            KeyCode::PageDown => self.move_by(20),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX / 2),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self.open_selected(),
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => self.go_up(),
+            // Same sort orders as the GUI; pressing a key twice flips the direction
+            KeyCode::Char('n') => self.set_sort(match self.sort {
+                SortMethod::NameAZ => SortMethod::NameZA,
+                _ => SortMethod::NameAZ,
+            }),
+            KeyCode::Char('s') => self.set_sort(match self.sort {
+                SortMethod::SizeLargest => SortMethod::SizeSmallest,
+                _ => SortMethod::SizeLargest,
+            }),
+            KeyCode::Char('t') => self.set_sort(match self.sort {
+                SortMethod::Newest => SortMethod::Oldest,
+                _ => SortMethod::Newest,
+            }),
+            KeyCode::Char('d') | KeyCode::Delete => self.pending_delete = self.selected(),
+            _ => {}
+        }
+        true
+    }
+}
+
+fn draw_tui(frame: &mut ratatui::Frame, app: &mut TuiApp) {
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Color as TuiColor, Modifier, Style};
+    use ratatui::text::Line;
+    use ratatui::widgets::{List, ListItem, Paragraph};
+
+    let unit = app.config.unit;
+    let [header, body, footer] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+    let dir = app.tree.node(app.cursor);
+    let title = format!(
+        " {}  {:.2} {}, {} files  [{}]",
+        dir.info.path,
+        unit.convert(dir.info.size),
+        unit,
+        dir.info.file_count,
+        app.sort
+    );
+    frame.render_widget(
+        Paragraph::new(title).style(Style::default().fg(TuiColor::Black).bg(TuiColor::Cyan)),
+        header,
+    );
+
+    let parent_size = dir.info.size.max(1);
+    let items: Vec<ListItem> = app
+        .children
+        .iter()
+        .map(|&id| {
+            let node = app.tree.node(id);
+            let filled = ((node.info.size as f64 / parent_size as f64) * TUI_BAR_WIDTH as f64).round() as usize;
+            let bar = format!("{}{}", "#".repeat(filled), " ".repeat(TUI_BAR_WIDTH - filled.min(TUI_BAR_WIDTH)));
+            let name = if node.info.is_dir { format!("{}/", node.name) } else { node.name.clone() };
+            let line = format!("{:>10.2} {} [{}] {}", unit.convert(node.info.size), unit, bar, name);
+            let style = if node.info.is_dir {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::styled(line, style))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, body, &mut app.list);
+
+    let (footer_text, footer_style) = match app.pending_delete {
+        Some(id) => {
+            let node = app.tree.node(id);
+            let action = if app.config.deletes_permanently() { "Delete permanently" } else { "Move to the Trash" };
+            (
+                format!(" {} {} ({:.2} {})? y/n", action, node.info.path, unit.convert(node.info.size), unit),
+                Style::default().fg(TuiColor::White).bg(TuiColor::Red),
+            )
+        }
+        None if !app.status.is_empty() => (format!(" {}", app.status), Style::default().fg(TuiColor::Yellow)),
+        None => (
+            " ↑↓ move  → open  ← back  n/s/t sort by name/size/time  d delete  q quit".to_string(),
+            Style::default().fg(TuiColor::DarkGray),
+        ),
+    };
+    frame.render_widget(Paragraph::new(footer_text).style(footer_style), footer);
+}
+
+pub(crate) fn run_tui(config: AppConfig, result: ScanResult) -> Result<(), String> {
+    use ratatui::crossterm::event::{self, Event, KeyEventKind};
+
+    let mut app = TuiApp::new(config, result);
+    // Also installs a panic hook that puts the terminal back
+    let mut terminal = ratatui::init();
+    let outcome = (|| -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| draw_tui(frame, &mut app))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !app.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    outcome.map_err(|e| format!("Terminal error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use disk_maid::scanner::{scan_directory, CancelToken, ScanOptions};
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use std::{
+        fs,
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    // big/ (x: 300 bytes, inner/y: 200 bytes) and small.txt (50 bytes), with big older than small.txt
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("disk_maid_tui_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("big/inner")).unwrap();
+        fs::write(dir.join("big/x"), vec![0u8; 300]).unwrap();
+        fs::write(dir.join("big/inner/y"), vec![0u8; 200]).unwrap();
+        fs::write(dir.join("small.txt"), vec![0u8; 50]).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        fs::File::open(dir.join("big")).unwrap().set_modified(old).unwrap();
+        dir
+    }
+
+    fn app(dir: &Path) -> TuiApp {
+        let options = ScanOptions {
+            threads: 1,
+            ..Default::default()
+        };
+        let result = scan_directory(dir.to_path_buf(), &options, &CancelToken::default(), |_| {}).unwrap();
+        let config = AppConfig {
+            default_sort: SortMethod::NameAZ,
+            permanent_delete: true,
+            ..Default::default()
+        };
+        TuiApp::new(config, result)
+    }
+
+    fn press(app: &mut TuiApp, code: KeyCode) -> bool {
+        app.handle_key(KeyEvent::from(code))
+    }
+
+    fn names(app: &TuiApp) -> Vec<&str> {
+        app.children.iter().map(|&id| app.tree.node(id).name.as_str()).collect()
+    }
+
+    fn selected_name(app: &TuiApp) -> Option<&str> {
+        app.selected().map(|id| app.tree.node(id).name.as_str())
+    }
+
+    #[test]
+    fn keys_move_the_selection_and_walk_the_tree() {
+        let dir = fixture("navigation");
+        let mut app = app(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(names(&app), vec!["big", "small.txt"]);
+        assert_eq!(selected_name(&app), Some("big"));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Down);
+        assert_eq!(selected_name(&app), Some("small.txt"));
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(selected_name(&app), Some("big"));
+
+        // Opening a file does nothing, opening a folder lists it
+        press(&mut app, KeyCode::End);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.cursor, ScanTree::ROOT);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.tree.node(app.cursor).name, "big");
+        assert_eq!(names(&app), vec!["inner", "x"]);
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(names(&app), vec!["y"]);
+
+        // Going back selects the folder we came from
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(selected_name(&app), Some("inner"));
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.cursor, ScanTree::ROOT);
+        assert_eq!(selected_name(&app), Some("big"));
+
+        assert!(!press(&mut app, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn pressing_a_sort_key_twice_flips_the_direction() {
+        let dir = fixture("sorting");
+        let mut app = app(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let expected = [
+            ('s', SortMethod::SizeLargest, ["big", "small.txt"]),
+            ('s', SortMethod::SizeSmallest, ["small.txt", "big"]),
+            ('t', SortMethod::Newest, ["small.txt", "big"]),
+            ('t', SortMethod::Oldest, ["big", "small.txt"]),
+            ('n', SortMethod::NameAZ, ["big", "small.txt"]),
+            ('n', SortMethod::NameZA, ["small.txt", "big"]),
+        ];
+        for (key, sort, order) in expected {
+            press(&mut app, KeyCode::Char(key));
+            assert_eq!(app.sort, sort);
+            assert_eq!(names(&app), order, "{}", sort);
+            // The selected entry follows its row to the new position
+            assert_eq!(selected_name(&app), Some("big"), "{}", sort);
+        }
+    }
+
+    #[test]
+    fn answering_no_keeps_the_entry() {
+        let dir = fixture("delete_cancelled");
+        let mut app = app(&dir);
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.pending_delete, app.selected());
+        press(&mut app, KeyCode::Char('n'));
+        let kept = dir.join("big").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(kept);
+        assert_eq!(app.pending_delete, None);
+        assert_eq!(app.status, "Deletion cancelled.");
+        assert_eq!(app.sort, SortMethod::NameAZ);
+        assert_eq!(app.tree.node(ScanTree::ROOT).info.size, 550);
+    }
+
+    #[test]
+    fn answering_yes_deletes_and_rebuilds_the_totals() {
+        let dir = fixture("delete_confirmed");
+        let mut app = app(&dir);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(selected_name(&app), Some("inner"));
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        let inner_left = dir.join("big/inner").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!inner_left);
+        assert!(app.status.starts_with("Deleted"), "{}", app.status);
+        // Still inside big/, which now only holds x
+        assert_eq!(app.tree.node(app.cursor).name, "big");
+        assert_eq!(names(&app), vec!["x"]);
+        assert_eq!(selected_name(&app), Some("x"));
+        let big = app.tree.node(app.cursor);
+        assert_eq!((big.info.size, big.info.file_count), (300, 1));
+        let root = app.tree.node(ScanTree::ROOT);
+        assert_eq!((root.info.size, root.info.file_count), (350, 2));
+        assert!(app.files.iter().all(|f| !f.path.contains("inner")));
+    }
+}